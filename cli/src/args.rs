@@ -8,11 +8,20 @@ use clap::crate_name;
 use clap::crate_version;
 use clap::Args;
 use clap::{Parser, Subcommand};
+use clap_complete::ArgValueCompleter;
+use clap_complete::Shell;
+use contact_manager_lib::access::AccessMode;
+use contact_manager_lib::chrono::{DateTime, NaiveDate, Utc};
 use contact_manager_lib::paths::books_directory;
 use contact_manager_lib::paths::books_names;
+use contact_manager_lib::smart::{is_smart_book, smart_books_names, SMART_BOOK_PREFIX};
+use contact_manager_lib::template::Template;
 use contact_manager_lib::vcard::LogicalOperator;
 use contact_manager_lib::vcard_parser::vcard::property::Property;
 
+use crate::completion::{complete_book_names, complete_properties};
+use crate::config::config;
+use crate::listing::ShowFormat;
 use crate::APP_SHORTNAME;
 use crate::interactive::book::ShortCutArgBook;
 #[derive(Parser)]
@@ -21,15 +30,36 @@ use crate::interactive::book::ShortCutArgBook;
 #[command(version = crate_version!())]
 #[command(about = crate_description!(), long_about = None)]
 #[command(next_line_help = true)]
+#[command(after_long_help = EXAMPLES)]
 #[group(required = false, multiple = false)]
 pub struct Cli {
     #[command(subcommand)]
     pub immediate_mode: Option<ImmediateMode>,
+    /// refuse every modification of the books and contacts.
+    #[arg(long, global = true)]
+    pub read_only: bool,
+    /// refuse every reading of the contacts, they can only be created or modified.
+    #[arg(long, global = true, conflicts_with = "read_only")]
+    pub write_only: bool,
+}
+
+impl Cli {
+    /// what the command is allowed to do on the contacts.
+    pub fn access_mode(&self) -> AccessMode {
+        if self.read_only {
+            AccessMode::ReadOnly
+        } else if self.write_only {
+            AccessMode::WriteOnly
+        } else {
+            AccessMode::ReadWrite
+        }
+    }
 }
 
 #[derive(Args)]
 pub struct Book {
-    #[arg(value_name = "BOOK NAME VALUE", name = "book-name", short, long, required=false, value_parser = book_name_parser)]
+    /// name of an existing book. If omitted, all books are used for searching and the default book for adding.
+    #[arg(value_name = "BOOK NAME VALUE", name = "book-name", short, long, required=false, value_parser = book_name_parser, add = ArgValueCompleter::new(complete_book_names))]
     pub name: String,
 }
 
@@ -43,66 +73,210 @@ impl std::ops::Deref for Book {
 impl Default for Book {
     fn default() -> Self {
         Book {
-            name: "default".to_string(),
+            name: config().default_book().to_string(),
         }
     }
 }
 
+#[derive(Args)]
+pub struct FromTo {
+    /// name of the book where the contacts are searched, it can be the default book.
+    #[arg(value_name = "BOOK NAME VALUE", long, value_parser = book_name_or_default_parser, add = ArgValueCompleter::new(complete_book_names))]
+    pub from: String,
+    /// name of the book receiving the contacts, it can be the default book.
+    #[arg(value_name = "BOOK NAME VALUE", long, value_parser = book_name_or_default_parser, add = ArgValueCompleter::new(complete_book_names))]
+    pub to: String,
+}
+
 #[derive(Args)]
 pub struct Logic {
-    #[arg(
-        value_name = "LOGICAL OPERATOR",
-        value_enum,
-        default_value_t,
-        short,
-        long
-    )]
-    pub operator: LogicalOperator,
+    /// how the filters are combined to match a contact, 'or' if not configured otherwise.
+    #[arg(value_name = "LOGICAL OPERATOR", value_enum, short, long)]
+    pub operator: Option<LogicalOperator>,
+}
+
+impl Logic {
+    /// the operator given, or the one of the configuration.
+    pub fn operator(&self) -> LogicalOperator {
+        self.operator.clone().unwrap_or_else(|| config().operator())
+    }
+}
+#[derive(Subcommand)]
+pub enum ConfigAction {
+    /// print the value of a key, example: default-book or aliases.clients.
+    Get {
+        #[arg(value_name = "KEY")]
+        key: String,
+    },
+    /// set the value of a key, in TOML or as a simple text. An empty value removes the key.
+    Set {
+        #[arg(value_name = "KEY")]
+        key: String,
+        #[arg(value_name = "VALUE")]
+        value: String,
+    },
+    /// print the configuration in use, with the default values.
+    Show,
+}
+#[derive(Subcommand)]
+pub enum TagAction {
+    /// add tags to the contacts matched by the filters, their other tags are kept.
+    Add {
+        /// tags to add.
+        #[arg(value_name = "TAG", required = true)]
+        tags: Vec<String>,
+        #[command(flatten)]
+        book: Option<Book>,
+        #[command(flatten)]
+        find_filters: PropertyArg1,
+        #[command(flatten)]
+        lo: Logic,
+    },
+    /// remove tags from the contacts matched by the filters, their other tags are kept.
+    Remove {
+        /// tags to remove.
+        #[arg(value_name = "TAG", required = true)]
+        tags: Vec<String>,
+        #[command(flatten)]
+        book: Option<Book>,
+        #[command(flatten)]
+        find_filters: PropertyArg1,
+        #[command(flatten)]
+        lo: Logic,
+    },
+    /// rename a tag on every contact having it.
+    Rename {
+        /// present name of the tag.
+        #[arg(value_name = "TAG", long)]
+        from: String,
+        /// new name of the tag.
+        #[arg(value_name = "NEW TAG", long)]
+        to: String,
+        #[command(flatten)]
+        book: Option<Book>,
+    },
+    /// print every tag with the number of contacts having it, the most used first.
+    List {
+        #[command(flatten)]
+        book: Option<Book>,
+    },
+}
+#[derive(Subcommand)]
+pub enum SmartBookAction {
+    /// save the filters as a smart book, replacing the one of the same name.
+    Save {
+        /// name of the smart book, example: 'clients-in-lyon', read afterward as '@clients-in-lyon'.
+        #[arg(value_name = "SMART BOOK NAME", value_parser = smart_book_name_parser)]
+        name: String,
+        #[command(flatten)]
+        book: Option<Book>,
+        #[command(flatten)]
+        find_filters: PropertyArg1,
+        #[command(flatten)]
+        lo: Logic,
+    },
+    /// delete a smart book, the contacts are kept.
+    Delete {
+        /// name of the smart book.
+        #[arg(value_name = "SMART BOOK NAME", value_parser = smart_book_name_parser, add = ArgValueCompleter::new(complete_book_names))]
+        name: String,
+    },
+    /// print the smart books with their query.
+    List,
 }
 #[derive(Args)]
 pub struct PropertyArg1 {
-    // / example: TEL;VALUE=uri;TYPE=home
-    // / the pid and value will be ignored to compare. Parameters will be used to match.
-    // / for X-name, just use a name that will not be another standard name.
+    /// property in vcard syntax, example: 'TEL;TYPE=home:0600000000' or only 'TEL'.
+    /// the pid will be ignored to compare. Parameters will be used to match, an empty value matches any value.
+    /// for X-name, just use a name that will not be another standard name.
+    /// it can be omitted if a tag is given.
     #[arg(value_name = "PROPERTIES TO FILTER", 
         value_parser = convert_str_to_property, 
-        required = true, short, long)]
+        add = ArgValueCompleter::new(complete_properties),
+        required_unless_present = "tag", short, long)]
     pub filter: Vec<Property>,
+    /// values of the filters only need to be contained in the values of the contact.
     #[arg(long)]
     pub forgive: bool,
+    /// only match the contacts having this tag in their categories, can be repeated.
+    #[arg(value_name = "TAG", long)]
+    pub tag: Vec<String>,
+}
+
+impl PropertyArg1 {
+    /// true if given or if the configuration makes every search forgiving.
+    pub fn forgive(&self) -> bool {
+        self.forgive || config().forgive
+    }
 }
 #[derive(Args)]
 pub struct PropertyArg2 {
-    // / example: TEL;VALUE=uri;TYPE=home
-    // / the pid and value will be ignored to compare. Parameters will be used to match.
-    // / for X-name, just use a name that will not be another standard name.
-    #[arg(value_name = "PROPERTIES TO SHOW", value_parser = convert_str_to_property, required = true, short, long)]
+    /// property in vcard syntax, example: 'TEL;TYPE=home' or 'EMAIL:someone@example.com'.
+    /// the pid will be ignored to compare. Parameters will be used to match.
+    /// for X-name, just use a name that will not be another standard name.
+    #[arg(value_name = "PROPERTIES TO SHOW", value_parser = convert_str_to_property, add = ArgValueCompleter::new(complete_properties), required = true, short, long)]
     pub show: Vec<Property>,
 }
 
 #[derive(Subcommand)]
 pub enum ImmediateMode {
+    /// create a new empty book.
     #[command(arg_required_else_help = true)]
     NewBook {
+        /// name of the book to create, it must not exist.
         #[arg(value_name = "BOOK NAME VALUE",  required=true, value_parser = book_new_name_parser)]
         new_book: String
     },
+    /// rename a book, the contacts of the book are kept.
     RenameBook {
         #[command(flatten)]
         book: Book,
+        /// new name of the book, it must not exist.
         #[arg(value_name = "BOOK NAME VALUE", name = "new-name", short, long, required=true, value_parser = book_new_name_parser)]
         new_name: String
     },
+    /// delete a book. The contacts are only removed from the book, not deleted.
     DeleteBook {
         #[command(flatten)]
         book: Book,
     },
+    /// list the books with their number of contacts, size, last modification and information.
+    #[command(visible_alias = "books")]
+    ListBooks,
+    /// set the information of a book, only the given information is modified.
+    SetBookInfo {
+        #[command(flatten)]
+        book: Book,
+        /// what the book is used for.
+        #[arg(value_name = "DESCRIPTION", long)]
+        description: Option<String>,
+        /// color to display the book, a name like 'red' or an hex code like '#ff0000'.
+        #[arg(value_name = "COLOR", long)]
+        color: Option<String>,
+        /// categories given to the contacts created in the book, replacing the previous ones.
+        #[arg(value_name = "CATEGORY", long = "category")]
+        categories: Option<Vec<String>>,
+        /// name of the property used to sort the contacts of the book, example: 'N'.
+        #[arg(value_name = "PROPERTY NAME", long)]
+        sort_by: Option<String>,
+    },
+    /// mark a book as read-only: its contacts can't be modified, added or removed.
+    ReadOnlyBook {
+        #[command(flatten)]
+        book: Book,
+        /// make the book writable again.
+        #[arg(long)]
+        writable: bool,
+    },
+    /// create new contacts with their full names. Two contacts can not have the same full name.
     CreateContact {
         #[command(flatten)]
         book: Option<Book>,
+        /// full names of the contacts to create.
         #[arg(value_name = "FULL NAME VALUE", required(true))]
         value_fn: Vec<String>,
     },
+    /// delete the contacts matched by the filters, removing them from every book.
     DeleteContact {
         #[command(flatten)]
         book: Option<Book>,
@@ -111,6 +285,25 @@ pub enum ImmediateMode {
         #[command(flatten)]
         lo: Logic,
     },
+    /// add the contacts matched by the filters in a book to another book, keeping them in the first one.
+    Copy {
+        #[command(flatten)]
+        from_to: FromTo,
+        #[command(flatten)]
+        find_filters: PropertyArg1,
+        #[command(flatten)]
+        lo: Logic,
+    },
+    /// move the contacts matched by the filters in a book to another book.
+    Move {
+        #[command(flatten)]
+        from_to: FromTo,
+        #[command(flatten)]
+        find_filters: PropertyArg1,
+        #[command(flatten)]
+        lo: Logic,
+    },
+    /// add the contacts matched by the filters in all books to a book.
     Addto {
         #[command(flatten)]
         book: Book,
@@ -119,6 +312,8 @@ pub enum ImmediateMode {
         #[command(flatten)]
         lo: Logic,
     },
+    /// remove the contacts matched by the filters from a book.
+    /// a contact which is not in any book anymore is deleted.
     Removefrom {
         #[command(flatten)]
         book: Book,
@@ -127,7 +322,10 @@ pub enum ImmediateMode {
         #[command(flatten)]
         lo: Logic,
     },
+    /// show properties of the contacts matched by the filters.
+    /// without --pretty, the uid of each contact is printed followed by the properties, contacts separated by an empty line.
     FindValue {
+        /// user friendly output, only the values and the full name if multiple contacts are found.
         #[arg(value_name = "pretty", long, short, default_value_t)]
         pretty: bool,
         #[command(flatten)]
@@ -138,7 +336,15 @@ pub enum ImmediateMode {
         lo: Logic,
         #[command(flatten)]
         show_filter: PropertyArg2,
+        /// only the contacts modified after this date or time, example: '2026-01-01' or '2026-01-01T12:00:00Z'.
+        /// contacts without a time of modification are always shown.
+        #[arg(value_name = "DATE", long, value_parser = date_time_parser)]
+        modified_since: Option<DateTime<Utc>>,
+        /// search the books nested in the book too.
+        #[arg(long, short, requires = "book-name")]
+        recursive: bool,
     },
+    /// add properties to the contacts matched by the filters, replacing them if the pid match.
     AddProperty {
         #[command(flatten)]
         book: Option<Book>,
@@ -149,6 +355,66 @@ pub enum ImmediateMode {
         #[command(flatten)]
         properties: PropertyArg2,
     },
+    /// set a property whose value is computed from the other properties of each contact matched by the filters.
+    /// The modifications are printed and confirmed before being applied.
+    BulkSet {
+        /// property in vcard syntax with placeholders, example: 'EMAIL;TYPE=work:{N.given|lower}.{N.family|lower}@corp.com' or 'N:{N.family|upper};{N.given};;;'.
+        /// components of N, ADR and GENDER can be given by name or index, filters are upper, lower, capitalize and trim.
+        #[arg(value_name = "TEMPLATE", value_parser = template_parser)]
+        template: Template,
+        #[command(flatten)]
+        book: Option<Book>,
+        #[command(flatten)]
+        find_filters: PropertyArg1,
+        #[command(flatten)]
+        lo: Logic,
+        /// only print the modifications.
+        #[arg(long)]
+        dry_run: bool,
+        /// apply the modifications without confirmation.
+        #[arg(long, short, conflicts_with = "dry_run")]
+        yes: bool,
+    },
+    /// replace text in the values of a property, in every contact or in the contacts matched by the filters.
+    /// The modifications are printed and confirmed before being applied.
+    Replace {
+        /// name of the property whose values are modified, example: 'EMAIL'.
+        #[arg(value_name = "PROPERTY NAME", long)]
+        property: String,
+        /// text to replace, a regular expression with --regex.
+        #[arg(value_name = "TEXT", long)]
+        from: String,
+        /// replacement, it can refer to the groups of the regular expression, example: '$1'.
+        #[arg(value_name = "TEXT", long)]
+        to: String,
+        /// read --from as a regular expression.
+        #[arg(long)]
+        regex: bool,
+        #[command(flatten)]
+        book: Option<Book>,
+        #[command(flatten)]
+        find_filters: Option<PropertyArg1>,
+        #[command(flatten)]
+        lo: Logic,
+        /// only print the modifications.
+        #[arg(long)]
+        dry_run: bool,
+        /// apply the modifications without confirmation.
+        #[arg(long, short, conflicts_with = "dry_run")]
+        yes: bool,
+    },
+    /// read or modify the configuration file.
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+    /// print the modifications applied to the contacts, the oldest first.
+    Journal {
+        /// only print the modifications applied after this date, example: 2026-01-01 or 2026-01-01T12:00:00Z.
+        #[arg(value_name = "DATE", long, value_parser = date_time_parser)]
+        since: Option<DateTime<Utc>>,
+    },
+    /// remove properties from the contacts matched by the filters.
     RemoveProperty {
         #[command(flatten)]
         book: Option<Book>,
@@ -159,22 +425,100 @@ pub enum ImmediateMode {
         #[command(flatten)]
         properties: PropertyArg2,
     },
+    /// print one line per contact with the values of the properties separated by a tab.
+    /// a contact is only rendered if every property exist, useful for email clients.
     GenerateIndex {
         #[command(flatten)]
         book: Option<Book>,
-        #[command(flatten)]
-        properties: PropertyArg1,
+        /// property to write in the index, in vcard syntax, can be repeated.
+        /// the index columns of the configuration are used if none is given.
+        #[arg(value_name = "PROPERTIES TO FILTER",
+            value_parser = convert_str_to_property,
+            add = ArgValueCompleter::new(complete_properties),
+            short = 'f', long = "filter")]
+        properties: Vec<Property>,
     },
+    /// import every vcard of a file into a book. Nothing is imported if one vcard is invalid.
     Import {
-    #[arg(value_name = "PATH OF FILE TO IMPORT")]
+        /// path of a file containing one or more vcards.
+        #[arg(value_name = "PATH OF FILE TO IMPORT")]
         path_vcards_file: PathBuf,
         #[command(flatten)]
         book: Option<Book>,
+        /// URI identifying where the vcards come from, example: 'urn:uuid:...' or the url of a server.
+        /// existing contacts are merged with the imported ones instead of being replaced, property by property.
+        #[arg(value_name = "SOURCE URI", long)]
+        source: Option<String>,
+    },
+    /// print the whole contacts matched by the filters, with the books they belong to.
+    Show {
+        #[command(flatten)]
+        book: Option<Book>,
+        #[command(flatten)]
+        find_filters: PropertyArg1,
+        #[command(flatten)]
+        lo: Logic,
+        /// how the contacts are printed.
+        #[arg(value_name = "FORMAT", value_enum, default_value_t, long)]
+        format: ShowFormat,
+        /// search the books nested in the book too.
+        #[arg(long, short, requires = "book-name")]
+        recursive: bool,
+    },
+    /// manage the tags of the contacts, kept in the CATEGORIES property.
+    Tag {
+        #[command(subcommand)]
+        action: TagAction,
+    },
+    /// manage the smart books, queries saved by name and read like read-only books named with a leading '@'.
+    SmartBook {
+        #[command(subcommand)]
+        action: SmartBookAction,
     },
+    /// print how many contacts have each property.
+    Stats {
+        #[command(flatten)]
+        book: Option<Book>,
+        /// count the contacts of the books nested in the book too.
+        #[arg(long, short, requires = "book-name")]
+        recursive: bool,
+    },
+    /// print every vcard of a book, or of all books.
     Export {
         #[command(flatten)]
         book: Option<Book>,
+        /// include the contacts of the books nested in the book.
+        #[arg(long, short, requires = "book-name")]
+        recursive: bool,
+    },
+    /// add or update contacts from the From, Reply-To and Cc headers of an email.
+    /// existing contacts are matched by email first, then by full name.
+    AddFromEmail {
+        /// path of the email, the email is read from the standard input if absent.
+        #[arg(value_name = "PATH OF EMAIL FILE, STDIN IF ABSENT")]
+        path_email: Option<PathBuf>,
+        #[command(flatten)]
+        book: Option<Book>,
+        /// apply the modifications without confirmation.
+        #[arg(long, short)]
+        yes: bool,
+    },
+    /// print the script to source in the shell to enable completions.
+    Completions {
+        /// shell to generate the completions for.
+        #[arg(value_name = "SHELL", value_enum)]
+        shell: Shell,
+    },
+    /// generate the man pages of cm and of every command.
+    Manpage {
+        /// directory where the man pages are written. The man page of cm is printed if absent.
+        #[arg(value_name = "OUTPUT DIRECTORY")]
+        output_dir: Option<PathBuf>,
     },
+    /// full screen interface to browse and modify books and contacts.
+    #[cfg(feature = "tui")]
+    Tui,
+    /// open directly a menu of the interactive mode on a book.
     #[cfg(feature = "interact")]
     Shortcut {
     #[clap(flatten)]
@@ -184,6 +528,27 @@ pub enum ImmediateMode {
 }
 }
 
+const EXAMPLES: &str = "Examples:
+
+  Find the phone numbers of a contact from a part of the full name:
+    cm find-value -f 'FN:Jean' -s TEL --forgive --pretty
+
+  Add a work email to a contact:
+    cm add-property -f 'FN:Jean Dupont' -s 'EMAIL;TYPE=work:jean@example.com'
+
+  Generate an index of emails and full names for an email client:
+    cm generate-index -f EMAIL -f FN
+
+  Add a contact to the book friends with the interactive menu:
+    cm shortcut --shortcut-mut add-a-client -b friends
+
+  Add the senders of an email from neomutt:
+    cm add-from-email --yes < message.eml
+
+  Make 'cm clients' print the emails of the book clients:
+    cm config set aliases.clients \"find-value -b clients -f FN -s EMAIL --pretty\"
+";
+
 fn convert_str_to_property(str: &str) -> Result<Property> {
     if !str.contains(":") {
         return Ok(Property::default(str))
@@ -194,7 +559,8 @@ fn convert_str_to_property(str: &str) -> Result<Property> {
 }
 
 fn book_name_parser(str: &str) -> Result<String> {
-    let names = books_names(APP_SHORTNAME)?;
+    let mut names = books_names(APP_SHORTNAME)?;
+    names.extend(smart_books_names(APP_SHORTNAME)?);
     if str == Book::default().name {
         bail!("You can't use the name for the default book (to prevent accidental deletion or renaming of the default book). If you want to search in all books, omit the --book-name argument.")
     }
@@ -205,7 +571,17 @@ fn book_name_parser(str: &str) -> Result<String> {
     bail!("the book name \"{str}\" doesn't exist in the directory {}, you can create it with create-book.\nPresent book names:\n{}", books_directory(APP_SHORTNAME)?.display(), names.join("\n"))
 }
 
+fn book_name_or_default_parser(str: &str) -> Result<String> {
+    if str == Book::default().name {
+        return Ok(str.to_string())
+    }
+    book_name_parser(str)
+}
+
 pub fn book_new_name_parser(str: &str) -> Result<String> {
+    if is_smart_book(str) {
+        bail!("the book name \"{str}\" can not start with {SMART_BOOK_PREFIX}, it is kept for smart books.")
+    }
     let names = books_names(APP_SHORTNAME)?;
     let string = str.to_string();
         if !names.contains(&string) {
@@ -213,3 +589,29 @@ pub fn book_new_name_parser(str: &str) -> Result<String> {
     } 
     bail!("the book name \"{str}\" already exist in the directory {}, you must precise a non existent name.\nPresent book names:\n{}", books_directory(APP_SHORTNAME)?.display(), names.join("\n"))
 }
+
+fn template_parser(str: &str) -> Result<Template> {
+    Ok(Template::parse(str)?)
+}
+
+fn smart_book_name_parser(str: &str) -> Result<String> {
+    let name = if is_smart_book(str) {
+        str.to_string()
+    } else {
+        format!("{SMART_BOOK_PREFIX}{str}")
+    };
+    if name.len() == SMART_BOOK_PREFIX.len() || name.contains('/') {
+        bail!("\"{str}\" is not a valid name for a smart book.")
+    }
+    Ok(name)
+}
+
+fn date_time_parser(str: &str) -> Result<DateTime<Utc>> {
+    if let Ok(date) = NaiveDate::parse_from_str(str, "%Y-%m-%d") {
+        return Ok(date.and_hms_opt(0, 0, 0).expect("midnight is valid").and_utc())
+    }
+    match DateTime::parse_from_rfc3339(str) {
+        Ok(date) => Ok(date.to_utc()),
+        Err(_) => bail!("\"{str}\" is not a date like 2026-01-01 or a time like 2026-01-01T12:00:00Z"),
+    }
+}
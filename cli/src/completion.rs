@@ -0,0 +1,68 @@
+use std::ffi::OsStr;
+
+use anyhow::{Context, Result};
+use clap_complete::{env::Shells, CompletionCandidate, Shell};
+use contact_manager_lib::{
+    api_tools::HasConst,
+    paths::books_names,
+    smart::smart_books_names,
+    vcard_parser::{constants::PropertyName, traits::HasValue},
+    vcards_from_book,
+};
+
+use crate::{args::Book, store, APP_SHORTNAME};
+
+/// name of the environment variable used by the shell to ask for completions.
+const COMPLETE_VAR: &str = "COMPLETE";
+
+/// print the script to source in the shell to register the completions.
+/// The script calls back the binary, so completions of book names and contacts are always up to date.
+pub fn print_completions(shell: Shell) -> Result<()> {
+    let shells = Shells::builtins();
+    let completer = shells
+        .completer(&shell.to_string())
+        .with_context(|| format!("completions are not available for {shell}"))?;
+    let bin = std::env::current_exe()?;
+    completer.write_registration(
+        COMPLETE_VAR,
+        APP_SHORTNAME,
+        APP_SHORTNAME,
+        &bin.to_string_lossy(),
+        &mut std::io::stdout(),
+    )?;
+    Ok(())
+}
+
+/// complete the names of existing books and smart books, except the default one which can not be given as argument.
+pub fn complete_book_names(current: &OsStr) -> Vec<CompletionCandidate> {
+    let current = current.to_string_lossy();
+    books_names(APP_SHORTNAME)
+        .unwrap_or_default()
+        .into_iter()
+        .chain(smart_books_names(APP_SHORTNAME).unwrap_or_default())
+        .filter(|b| b != &Book::default().name && b.starts_with(current.as_ref()))
+        .map(CompletionCandidate::new)
+        .collect()
+}
+
+/// complete the names of properties, or the full names of contacts if the property FN is already given.
+pub fn complete_properties(current: &OsStr) -> Vec<CompletionCandidate> {
+    let current = current.to_string_lossy();
+    let prefix_fn = format!("{}:", PropertyName::FN);
+    if current.to_uppercase().starts_with(&prefix_fn) {
+        let value = &current[prefix_fn.len()..];
+        return vcards_from_book(store(), None, false)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|v| v.get_property_by_name(PropertyName::FN))
+            .map(|p| p.get_value().to_string())
+            .filter(|fullname| fullname.starts_with(value))
+            .map(|fullname| CompletionCandidate::new(format!("{prefix_fn}{fullname}")))
+            .collect();
+    }
+    PropertyName::to_strings()
+        .into_iter()
+        .filter(|name| name.starts_with(&current.to_uppercase()))
+        .map(CompletionCandidate::new)
+        .collect()
+}
@@ -0,0 +1,252 @@
+use std::{collections::BTreeMap, fs, path::PathBuf, sync::OnceLock};
+
+use anyhow::{bail, Context, Result};
+use clap::CommandFactory;
+use contact_manager_lib::{
+    paths::{config_directory, set_data_directory},
+    smart::is_smart_book,
+    store::write_atomic,
+    template::Template,
+    vcard::LogicalOperator,
+};
+use serde::{Deserialize, Serialize};
+use toml::{Table, Value};
+use toml_edit::DocumentMut;
+
+use crate::{args::Cli, phone, APP_SHORTNAME};
+
+/// name of the configuration file, in the configuration directory of the application.
+pub const CONFIG_FILE: &str = "config.toml";
+/// book used when none is given and none is configured.
+const DEFAULT_BOOK: &str = "default";
+/// line naming a contact in the pretty output when none is configured.
+const DEFAULT_DISPLAY_CONTACT: &str = "{FN}:";
+
+/// settings of cm, every one is optional.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Config {
+    /// directory of the contacts and books, instead of $XDG_DATA_HOME/cm.
+    pub data_directory: Option<PathBuf>,
+    /// book used to add contacts when no book is given.
+    pub default_book: Option<String>,
+    /// how the filters are combined when no operator is given.
+    pub operator: Option<LogicalOperator>,
+    /// values of the filters only need to be contained in the values of the contacts, without --forgive.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub forgive: bool,
+    /// ISO code of the region of the phone numbers, example: 'FR'.
+    /// Numbers of this region are written in their national form in the pretty output.
+    pub phone_region: Option<String>,
+    /// template of the line naming a contact in the pretty output, example: '{FN} <{EMAIL}>'.
+    pub display_contact: Option<String>,
+    /// properties written in the index when generate-index is given none, example: ['EMAIL', 'FN'].
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub index_columns: Vec<String>,
+    /// commands replacing a name, example: clients = "find-value -b clients -f FN -s EMAIL".
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub aliases: BTreeMap<String, String>,
+}
+
+impl Config {
+    /// book used to add contacts when no book is given.
+    pub fn default_book(&self) -> &str {
+        self.default_book.as_deref().unwrap_or(DEFAULT_BOOK)
+    }
+    /// how the filters are combined when no operator is given.
+    pub fn operator(&self) -> LogicalOperator {
+        self.operator.clone().unwrap_or_default()
+    }
+    /// template of the line naming a contact in the pretty output.
+    pub fn display_contact(&self) -> Result<Template> {
+        Ok(Template::display(
+            self.display_contact
+                .as_deref()
+                .unwrap_or(DEFAULT_DISPLAY_CONTACT),
+        )?)
+    }
+    /// refuse the values which can be read but not used.
+    fn check(&self) -> Result<()> {
+        self.display_contact()?;
+        if let Some(region) = &self.phone_region {
+            if !phone::is_known_region(region) {
+                bail!("the phone region {region} is unknown, it must be an ISO code like 'FR' or 'US'.")
+            }
+        }
+        if let Some(book) = &self.default_book {
+            if book.trim().is_empty() || is_smart_book(book) {
+                bail!("the default book \"{book}\" is not a valid name of book.")
+            }
+        }
+        let command = Cli::command();
+        for name in self.aliases.keys() {
+            if command.find_subcommand(name).is_some() {
+                bail!("the alias {name} would hide the command of the same name.")
+            }
+        }
+        Ok(())
+    }
+}
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+/// the configuration read at the start, the default one if there is no file.
+pub fn config() -> &'static Config {
+    CONFIG.get_or_init(Config::default)
+}
+
+/// path of the configuration file.
+pub fn config_path() -> Result<PathBuf> {
+    Ok(config_directory(APP_SHORTNAME)?.join(CONFIG_FILE))
+}
+
+/// read the configuration file and use its data directory, before any access to the contacts.
+/// An invalid file is reported and the default configuration is used, so it can be fixed with `cm config set`.
+pub fn load() -> Result<()> {
+    let config = read().unwrap_or_else(|e| {
+        eprintln!("{e:#}, the default configuration is used.");
+        Config::default()
+    });
+    if let Some(directory) = &config.data_directory {
+        set_data_directory(directory.to_owned())?;
+    }
+    // the configuration is loaded once, at the start.
+    let _ = CONFIG.set(config);
+    Ok(())
+}
+
+fn read() -> Result<Config> {
+    let path = config_path()?;
+    if !path.exists() {
+        return Ok(Config::default());
+    }
+    let config: Config = toml::from_str(&fs::read_to_string(&path)?)
+        .with_context(|| format!("the configuration file {} is invalid", path.display()))?;
+    config.check()?;
+    Ok(config)
+}
+
+/// the value of a key of the configuration file, written in TOML. Keys of aliases are written 'aliases.NAME'.
+pub fn get(key: &str) -> Result<Option<String>> {
+    let table = Table::try_from(read()?)?;
+    let mut value = None;
+    for (n, part) in key.split('.').enumerate() {
+        value = match (n, value) {
+            (0, _) => table.get(part),
+            (_, Some(Value::Table(t))) => t.get(part),
+            _ => None,
+        };
+    }
+    Ok(value.map(|v| match v {
+        Value::String(s) => s.to_owned(),
+        v => v.to_string(),
+    }))
+}
+
+/// set the value of a key in the configuration file, written in TOML or as a simple text.
+/// An empty value removes the key. The rest of the file, with its comments, is kept as written.
+pub fn set(key: &str, value: &str) -> Result<()> {
+    let path = config_path()?;
+    let mut document = if path.exists() {
+        fs::read_to_string(&path)?
+            .parse::<DocumentMut>()
+            .with_context(|| format!("the configuration file {} is invalid", path.display()))?
+    } else {
+        DocumentMut::new()
+    };
+    let mut parts: Vec<&str> = key.split('.').collect();
+    let last = parts.pop().expect("split returns at least one part");
+    let mut current = document.as_table_mut();
+    for part in parts {
+        let Some(table) = current
+            .entry(part)
+            .or_insert(toml_edit::table())
+            .as_table_mut()
+        else {
+            bail!("{part} is not a table of the configuration.")
+        };
+        current = table;
+    }
+    if value.is_empty() {
+        // removing a key is always allowed, it can be the one making the file invalid.
+        current.remove(last);
+    } else {
+        current.insert(last, toml_edit::value(parse_value(value)));
+        let config: Config = toml::from_str(&document.to_string())
+            .with_context(|| format!("{value} is not a valid value for {key}"))?;
+        config.check()?;
+    }
+    fs::create_dir_all(
+        path.parent()
+            .expect("the configuration file is in a directory"),
+    )?;
+    // an interrupted write must not leave a truncated file, replaced by the default configuration at the next start.
+    write_atomic(&path, &document.to_string())?;
+    Ok(())
+}
+
+/// a value written in TOML, like ['EMAIL', 'FN'] or true, or else a text.
+fn parse_value(value: &str) -> toml_edit::Value {
+    value
+        .parse::<toml_edit::Value>()
+        .unwrap_or_else(|_| value.into())
+}
+
+/// the configuration in use, the values not configured being shown with their default.
+pub fn show() -> Result<String> {
+    let mut config = read()?;
+    config
+        .default_book
+        .get_or_insert_with(|| DEFAULT_BOOK.to_string());
+    config.operator.get_or_insert_with(LogicalOperator::default);
+    config
+        .display_contact
+        .get_or_insert_with(|| DEFAULT_DISPLAY_CONTACT.to_string());
+    Ok(format!(
+        "# {}\n{}",
+        config_path()?.display(),
+        toml::to_string_pretty(&config)?
+    ))
+}
+
+/// replace an alias given as first argument by its command.
+pub fn expand_aliases(args: Vec<String>) -> Vec<String> {
+    let Some(command) = args.get(1).and_then(|name| config().aliases.get(name)) else {
+        return args;
+    };
+    let mut expanded = vec![args[0].to_owned()];
+    expanded.extend(split_arguments(command));
+    expanded.extend(args.into_iter().skip(2));
+    expanded
+}
+
+/// split a command on the spaces, except in quotes.
+fn split_arguments(command: &str) -> Vec<String> {
+    let mut arguments = Vec::new();
+    let mut argument = String::new();
+    let mut quote = None;
+    let mut started = false;
+    for c in command.chars() {
+        match (quote, c) {
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                started = true;
+            }
+            (Some(q), c) if c == q => quote = None,
+            (None, c) if c.is_whitespace() => {
+                if started {
+                    arguments.push(std::mem::take(&mut argument));
+                    started = false;
+                }
+            }
+            (_, c) => {
+                argument.push(c);
+                started = true;
+            }
+        }
+    }
+    if started {
+        arguments.push(argument);
+    }
+    arguments
+}
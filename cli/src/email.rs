@@ -0,0 +1,429 @@
+use std::fmt::Display;
+use std::io::Read;
+use std::path::Path;
+
+use anyhow::{bail, Result};
+use contact_manager_lib::{
+    changes::{Change, ChangeSet},
+    find_books_where_contact_is_present, find_uids, find_uids_by_email,
+    uuid::Uuid,
+    vcard::LogicalOperator,
+    vcard_parser::vcard::property::Property,
+};
+use mailparse::{addrparse_header, parse_headers, MailAddr, MailHeaderMap};
+
+use crate::{confirm_each, store, APP_SHORTNAME};
+
+/// headers of the email where the addresses are taken from.
+const HEADERS_ADDRESSES: [&str; 3] = ["From", "Reply-To", "Cc"];
+
+/// an address found in the headers of an email, with the display name decoded.
+#[derive(Clone)]
+pub struct Sender {
+    pub name: Option<String>,
+    pub email: String,
+}
+
+impl Sender {
+    /// display name of the address, if it is not empty.
+    fn display_name(&self) -> Option<&str> {
+        self.name
+            .as_deref()
+            .map(str::trim)
+            .filter(|n| !n.is_empty())
+    }
+    /// name to use as FN if the contact must be created, the local part of the email if there is no display name.
+    fn fullname(&self) -> String {
+        self.display_name()
+            .unwrap_or_else(|| self.email.split('@').next().unwrap_or(&self.email))
+            .to_string()
+    }
+}
+
+/// a contact found for an address.
+#[derive(Clone, Copy)]
+struct Known {
+    uuid: Uuid,
+    /// the contact is already in the book.
+    in_book: bool,
+}
+
+/// what must be done for an address to be present in the book.
+#[derive(Clone)]
+pub enum Action {
+    /// a contact already has this email, it only needs to be added to the book.
+    AddToBook(Sender, Uuid),
+    /// a contact has the same full name, the email will be added to it.
+    AddEmail {
+        sender: Sender,
+        uuid: Uuid,
+        /// the contact is not in the book yet.
+        add_to_book: bool,
+        /// the contact is created by a previous action, for another address with the same display name.
+        new_contact: bool,
+    },
+    /// no contact matched, a new one will be created with this uid.
+    Create(Sender, Uuid),
+}
+
+impl Display for Action {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Action::AddToBook(s, _) => write!(f, "add {} <{}> to the book", s.fullname(), s.email),
+            Action::AddEmail {
+                sender: s,
+                new_contact,
+                ..
+            } => {
+                write!(
+                    f,
+                    "add email <{}> to {} contact {}",
+                    s.email,
+                    if *new_contact { "new" } else { "existing" },
+                    s.fullname()
+                )
+            }
+            Action::Create(s, _) => write!(f, "create contact {} <{}>", s.fullname(), s.email),
+        }
+    }
+}
+
+/// read the email from the path or from stdin if none is given and add or update the contacts of the addresses found.
+pub fn add_from_email(path: Option<&Path>, book_name: &str, yes: bool) -> Result<()> {
+    let raw = if let Some(path) = path {
+        std::fs::read(path)?
+    } else {
+        let mut raw = Vec::new();
+        std::io::stdin().read_to_end(&mut raw)?;
+        raw
+    };
+    let senders = senders_from_email(&raw)?;
+    if senders.is_empty() {
+        bail!(
+            "no address found in the headers {}",
+            HEADERS_ADDRESSES.join(", ")
+        );
+    }
+    let actions = plan_actions(&senders, book_name)?;
+    if actions.is_empty() {
+        println!("every address is already present in the book {book_name}");
+        return Ok(());
+    }
+    let actions = if yes {
+        actions
+    } else {
+        confirm_each("Modifications to apply:", actions)?
+    };
+    // the modifications are applied at once, and written to the journal.
+    let mut changes = changes_of(actions, book_name)?;
+    print!("{}", changes.diff(APP_SHORTNAME)?);
+    changes.apply(store())?;
+    Ok(())
+}
+
+/// parse the headers of the email and return the addresses, without duplicates.
+pub fn senders_from_email(raw: &[u8]) -> Result<Vec<Sender>> {
+    let (headers, _) = parse_headers(raw)?;
+    let mut senders: Vec<Sender> = Vec::new();
+    for name in HEADERS_ADDRESSES {
+        for header in headers.get_all_headers(name) {
+            for addr in addrparse_header(header)?.iter() {
+                let infos = match addr {
+                    MailAddr::Single(info) => vec![info.clone()],
+                    MailAddr::Group(group) => group.addrs.clone(),
+                };
+                for info in infos {
+                    if !senders
+                        .iter()
+                        .any(|s| s.email.to_lowercase() == info.addr.to_lowercase())
+                    {
+                        senders.push(Sender {
+                            name: info.display_name,
+                            email: info.addr,
+                        });
+                    }
+                }
+            }
+        }
+    }
+    Ok(senders)
+}
+
+/// find for every address the action to do, matching existing contacts by EMAIL first, then by FN.
+fn plan_actions(senders: &[Sender], book_name: &str) -> Result<Vec<Action>> {
+    let known = |uuid: Option<&Uuid>| -> Result<Option<Known>> {
+        uuid.map(|uuid| {
+            Ok(Known {
+                uuid: *uuid,
+                in_book: in_book(uuid, book_name)?,
+            })
+        })
+        .transpose()
+    };
+    let by_email = |email: &str| -> Result<Option<Known>> {
+        known(find_uids_by_email(store(), None, email)?.first())
+    };
+    let by_fullname = |name: &str| -> Result<Option<Known>> {
+        let property_fn = Property::create_from_str(&format!("FN:{name}\n"))?;
+        known(
+            find_uids(
+                store(),
+                None,
+                false,
+                &vec![property_fn],
+                &LogicalOperator::Or,
+                false,
+            )?
+            .first(),
+        )
+    };
+    plan(senders, by_email, by_fullname)
+}
+
+/// the actions for every address. The addresses of a display name already planned to be created
+/// are added to the same new contact instead of creating another one.
+fn plan(
+    senders: &[Sender],
+    by_email: impl Fn(&str) -> Result<Option<Known>>,
+    by_fullname: impl Fn(&str) -> Result<Option<Known>>,
+) -> Result<Vec<Action>> {
+    let mut actions: Vec<Action> = Vec::new();
+    for sender in senders {
+        let created = sender.display_name().and_then(|name| {
+            actions.iter().find_map(|a| match a {
+                Action::Create(s, uuid) if s.display_name() == Some(name) => Some(*uuid),
+                _ => None,
+            })
+        });
+        let found = by_email(&sender.email)?;
+        let action = match created {
+            // an address already known stays with its contact.
+            Some(uuid) if found.is_none() => Some(Action::AddEmail {
+                sender: sender.clone(),
+                uuid,
+                add_to_book: false,
+                new_contact: true,
+            }),
+            _ => plan_action(sender, found, &by_fullname)?,
+        };
+        actions.extend(action);
+    }
+    Ok(actions)
+}
+
+/// the action to do for an address, from the contact having its email or else the contact having its display name.
+/// Without display name, the contacts are not matched by FN: the local part of the email is not a name.
+fn plan_action(
+    sender: &Sender,
+    by_email: Option<Known>,
+    by_fullname: impl Fn(&str) -> Result<Option<Known>>,
+) -> Result<Option<Action>> {
+    if let Some(known) = by_email {
+        return Ok((!known.in_book).then(|| Action::AddToBook(sender.clone(), known.uuid)));
+    }
+    let by_fullname = match sender.display_name() {
+        Some(name) => by_fullname(name)?,
+        None => None,
+    };
+    Ok(Some(match by_fullname {
+        Some(known) => Action::AddEmail {
+            sender: sender.clone(),
+            uuid: known.uuid,
+            add_to_book: !known.in_book,
+            new_contact: false,
+        },
+        None => Action::Create(sender.clone(), Uuid::new_v4()),
+    }))
+}
+
+fn in_book(uuid: &Uuid, book_name: &str) -> Result<bool> {
+    Ok(find_books_where_contact_is_present(uuid, APP_SHORTNAME)?.contains(&book_name.to_string()))
+}
+
+/// the changes of the actions kept. If the creation of a contact was not kept,
+/// the first address added to this new contact creates it.
+fn changes_of(actions: Vec<Action>, book_name: &str) -> Result<ChangeSet> {
+    let mut changes = ChangeSet::new();
+    let mut created: Vec<Uuid> = Vec::new();
+    let book = book_name.to_string();
+    for action in actions {
+        match action {
+            Action::AddToBook(_, uuid) => changes.push(Change::AddToBook {
+                uuid,
+                book: book.clone(),
+            }),
+            Action::AddEmail {
+                sender,
+                uuid,
+                add_to_book,
+                new_contact,
+            } => {
+                if new_contact && !created.contains(&uuid) {
+                    created.push(uuid);
+                    changes.push(Change::CreateContact {
+                        uuid,
+                        fullname: sender.fullname(),
+                        book: book.clone(),
+                    });
+                }
+                changes.push(add_email(&sender, uuid)?);
+                if add_to_book {
+                    changes.push(Change::AddToBook {
+                        uuid,
+                        book: book.clone(),
+                    });
+                }
+            }
+            Action::Create(sender, uuid) => {
+                created.push(uuid);
+                changes.push(Change::CreateContact {
+                    uuid,
+                    fullname: sender.fullname(),
+                    book: book.clone(),
+                });
+                changes.push(add_email(&sender, uuid)?);
+            }
+        }
+    }
+    Ok(changes)
+}
+
+fn add_email(sender: &Sender, uuid: Uuid) -> Result<Change> {
+    Ok(Change::AddProperty {
+        uuid,
+        property: Property::create_from_str(&format!("EMAIL:{}\n", sender.email))?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EMAIL: &[u8] = b"From: Jean Dupont <jean@example.com>\r\n\
+Reply-To: JEAN@example.com\r\n\
+Cc: john@example.com, Team: marie@example.com, \"Paul\" <paul@example.com>;\r\n\
+Subject: hello\r\n\
+\r\n\
+body\r\n";
+
+    fn sender(name: Option<&str>, email: &str) -> Sender {
+        Sender {
+            name: name.map(str::to_string),
+            email: email.to_string(),
+        }
+    }
+
+    fn known(in_book: bool) -> Known {
+        Known {
+            uuid: Uuid::new_v4(),
+            in_book,
+        }
+    }
+
+    #[test]
+    fn senders_without_duplicates() {
+        let senders = senders_from_email(EMAIL).unwrap();
+        let emails: Vec<&str> = senders.iter().map(|s| s.email.as_str()).collect();
+        // the Reply-To is the same address as the From, with another case.
+        assert_eq!(
+            emails,
+            [
+                "jean@example.com",
+                "john@example.com",
+                "marie@example.com",
+                "paul@example.com"
+            ]
+        );
+        assert_eq!(senders[0].display_name(), Some("Jean Dupont"));
+        assert_eq!(senders[1].display_name(), None);
+        assert_eq!(senders[3].display_name(), Some("Paul"));
+    }
+
+    #[test]
+    fn fullname_from_local_part() {
+        assert_eq!(sender(None, "john@example.com").fullname(), "john");
+        assert_eq!(sender(Some("  "), "john@example.com").fullname(), "john");
+        assert_eq!(
+            sender(Some("John Doe"), "john@example.com").fullname(),
+            "John Doe"
+        );
+    }
+
+    #[test]
+    fn plan_by_email() {
+        let no_fn = |_: &str| -> Result<Option<Known>> { panic!("the email was found") };
+        let s = sender(Some("Jean"), "jean@example.com");
+        assert!(plan_action(&s, Some(known(true)), no_fn).unwrap().is_none());
+        assert!(matches!(
+            plan_action(&s, Some(known(false)), no_fn).unwrap(),
+            Some(Action::AddToBook(..))
+        ));
+    }
+
+    #[test]
+    fn plan_by_display_name() {
+        let s = sender(Some("Jean Dupont"), "jean@example.com");
+        let action = plan_action(&s, None, |name| {
+            assert_eq!(name, "Jean Dupont");
+            Ok(Some(known(false)))
+        })
+        .unwrap();
+        assert!(matches!(
+            action,
+            Some(Action::AddEmail {
+                add_to_book: true,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn plan_without_display_name_creates() {
+        // a contact named "john" must not receive the address john@example.com.
+        let s = sender(None, "john@example.com");
+        let action = plan_action(&s, None, |_| Ok(Some(known(true)))).unwrap();
+        assert!(matches!(action, Some(Action::Create(..))));
+    }
+
+    #[test]
+    fn plan_same_display_name_creates_once() {
+        let senders = [
+            sender(Some("Alice"), "a@x.com"),
+            sender(Some("Alice"), "a@y.com"),
+        ];
+        let actions = plan(&senders, |_| Ok(None), |_| Ok(None)).unwrap();
+        let Action::Create(_, uuid) = actions[0] else {
+            panic!("the first address creates the contact")
+        };
+        assert!(matches!(
+            actions[1],
+            Action::AddEmail {
+                uuid: u,
+                add_to_book: false,
+                new_contact: true,
+                ..
+            } if u == uuid
+        ));
+        let changes = changes_of(actions, "friends").unwrap();
+        let created = changes
+            .changes()
+            .iter()
+            .filter(|c| matches!(c, Change::CreateContact { .. }))
+            .count();
+        assert_eq!(created, 1);
+        assert_eq!(changes.len(), 3);
+        assert!(changes.changes().iter().all(|c| c.uuid() == &uuid));
+        // the creation not kept is done by the next address.
+        let changes = changes_of(
+            plan(&senders, |_| Ok(None), |_| Ok(None))
+                .unwrap()
+                .into_iter()
+                .skip(1)
+                .collect(),
+            "friends",
+        )
+        .unwrap();
+        assert!(matches!(changes.changes()[0], Change::CreateContact { .. }));
+        assert_eq!(changes.len(), 2);
+    }
+}
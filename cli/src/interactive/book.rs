@@ -1,8 +1,10 @@
 use super::contact::VecContact;
+use super::search::search_book;
 use super::validator_new_bookname;
-use crate::APP_SHORTNAME;
+use crate::store;
 use anyhow::{bail, Result};
 use clap_shortcuts::clap_shortcuts_derive::ShortCuts;
+use contact_manager_lib::book::{set_book_info, BookInfo};
 use contact_manager_lib::vcard::uuids_from_vcards;
 use contact_manager_lib::{create_book, delete_book, rename_book};
 use inquire::{Select, Text};
@@ -10,13 +12,14 @@ use promptable::derive_more::Display;
 use promptable::promptable_derive::Promptable;
 use promptable::termion::screen::ToMainScreen;
 #[derive(Promptable, Clone, Display, ShortCuts)]
-#[display(fmt = "Book {}: {}", name, "contacts.len()")]
+#[display(fmt = "Book {}: {}{}", name, "contacts.len()", "book_description(info)")]
 #[prompt(trigger_del = "book_del(deleted)?")]
 #[shortcut(values(
     name = "Add a Client",
     func = "add_contact(&mut self.contacts, self.name.as_str())?"
 ))]
 #[shortcut(values(name = "Select a Client", func = "select_contact(&self.contacts)?"))]
+#[shortcut(values(name = "Search a Client", func = "search_book(self)?"))]
 pub struct Book {
     #[promptable(short_display = true)]
     #[promptable(function_new = "book_add()?")]
@@ -25,11 +28,32 @@ pub struct Book {
     #[promptable(function_new = "contacts_empty_vec()?")]
     #[promptable(function_mod = "VecContact::modify_by_prompt(field, self.name.as_str())?")]
     pub contacts: VecContact,
+    /// information of the book, read once with its contacts.
+    #[promptable(function_new = "book_info_new()?")]
+    #[promptable(function_mod = "book_info_mod(field, self.name.as_str())?")]
+    #[promptable(inspect = false)]
+    pub info: BookInfo,
+}
+
+/// name of the book indented by its depth in the tree of books, without the names of its parents.
+pub fn tree_name(name: &str) -> String {
+    match name.rsplit_once('/') {
+        Some((_, last)) => format!("{}└ {last}", "  ".repeat(name.matches('/').count())),
+        None => name.to_string(),
+    }
+}
+
+/// description of the book to display after the number of contacts, empty if there is none.
+fn book_description(info: &BookInfo) -> String {
+    info.description
+        .as_ref()
+        .map(|d| format!(" - {d}"))
+        .unwrap_or_default()
 }
 
 fn book_del(deleted_books: Vec<Book>) -> Result<()> {
     for book in deleted_books {
-        delete_book(&book.name, APP_SHORTNAME)?;
+        delete_book(&book.name, store())?;
     }
     Ok(())
 }
@@ -38,16 +62,34 @@ fn contacts_empty_vec() -> Result<Option<VecContact>> {
     Ok(Some(VecContact(Vec::new())))
 }
 
+fn book_info_new() -> Result<Option<BookInfo>> {
+    Ok(Some(BookInfo::default()))
+}
+
+/// modify the description of the book.
+fn book_info_mod(field: &mut BookInfo, name: &str) -> Result<()> {
+    if let Some(description) = Text::new("Description of the book:")
+        .with_initial_value(field.description.as_deref().unwrap_or_default())
+        .prompt_skippable()?
+    {
+        let mut info = field.clone();
+        info.description = (!description.trim().is_empty()).then_some(description);
+        set_book_info(store(), name, &info)?;
+        *field = info;
+    }
+    Ok(())
+}
+
 fn book_add() -> Result<Option<String>> {
     if let Some(name) = choose_new_bookname()? {
-        create_book(&name, APP_SHORTNAME)?;
+        create_book(&name, store())?;
         return Ok(Some(name));
     }
     Ok(None)
 }
 fn book_mod(field: &mut String) -> Result<()> {
     if let Some(new_name) = choose_new_bookname()? {
-        rename_book(&field, &new_name, APP_SHORTNAME)?;
+        rename_book(&field, &new_name, store())?;
         *field = new_name;
     }
 
@@ -0,0 +1,177 @@
+use anyhow::Result;
+use contact_manager_lib::{
+    access::is_book_read_only,
+    changes::{Change, ChangeSet},
+    tags::{tag_changes_of, TagOperation},
+    template::{template_changes, Template},
+    uuid::Uuid,
+    vcard_parser::vcard::Vcard,
+};
+use inquire::{validator::Validation, Confirm, MultiSelect, Select, Text};
+
+use super::{apply_in_memory, book::Book, contact_uuid, stage};
+use crate::APP_SHORTNAME;
+
+const BULK_COPY: &str = "Copy to another book";
+const BULK_MOVE: &str = "Move to another book";
+const BULK_REMOVE: &str = "Remove from this book";
+const BULK_TAG_ADD: &str = "Add tags";
+const BULK_TAG_REMOVE: &str = "Remove tags";
+const BULK_SET: &str = "Set a property from a template";
+
+/// select several contacts of a book and an action to do on all of them.
+/// The changes are shown before being confirmed, then kept pending like the other modifications.
+pub fn menu_bulk(books: &mut [Book]) -> Result<()> {
+    let names: Vec<String> = books.iter().map(|b| b.name.to_owned()).collect();
+    let Some(book) = Select::new("Book of the contacts:", names).prompt_skippable()? else {
+        return Ok(());
+    };
+    let contacts = books
+        .iter()
+        .find(|b| b.name == book)
+        .map(|b| b.contacts.to_vec())
+        .unwrap_or_default();
+    if contacts.is_empty() {
+        println!("No contacts in this book.");
+        return Ok(());
+    }
+    let Some(selected) = MultiSelect::new("Contacts:", contacts).prompt_skippable()? else {
+        return Ok(());
+    };
+    if selected.is_empty() {
+        return Ok(());
+    }
+    let uuids = selected
+        .iter()
+        .map(contact_uuid)
+        .collect::<Result<Vec<Uuid>>>()?;
+    // the contacts as shown, with the pending changes.
+    let vcards: Vec<Vcard> = selected.iter().map(|c| c.vcard.0.to_owned()).collect();
+    let mut options = vec![BULK_COPY];
+    // the contacts of a read-only book can not be moved or removed from it.
+    if !is_book_read_only(APP_SHORTNAME, &book)? {
+        options.extend([BULK_MOVE, BULK_REMOVE]);
+    }
+    options.extend([BULK_TAG_ADD, BULK_TAG_REMOVE, BULK_SET]);
+    let Some(action) = Select::new(&format!("Action on the {} contacts:", uuids.len()), options)
+        .without_filtering()
+        .prompt_skippable()?
+    else {
+        return Ok(());
+    };
+    let changes = match action {
+        BULK_COPY | BULK_MOVE => {
+            let Some(destination) = select_destination(books, &book)? else {
+                return Ok(());
+            };
+            let mut changes = ChangeSet::new();
+            for uuid in &uuids {
+                changes.push(Change::AddToBook {
+                    uuid: *uuid,
+                    book: destination.to_owned(),
+                });
+                if action == BULK_MOVE {
+                    changes.push(Change::RemoveFromBook {
+                        uuid: *uuid,
+                        book: book.to_owned(),
+                    });
+                }
+            }
+            changes
+        }
+        BULK_REMOVE => {
+            let mut changes = ChangeSet::new();
+            for uuid in &uuids {
+                changes.push(Change::RemoveFromBook {
+                    uuid: *uuid,
+                    book: book.to_owned(),
+                });
+            }
+            changes
+        }
+        BULK_TAG_ADD | BULK_TAG_REMOVE => {
+            let Some(tags) = input_tags()? else {
+                return Ok(());
+            };
+            let operation = if action == BULK_TAG_ADD {
+                TagOperation::Add(tags)
+            } else {
+                TagOperation::Remove(tags)
+            };
+            tag_changes_of(&vcards, &operation)?
+        }
+        _ => {
+            let Some(template) = input_template()? else {
+                return Ok(());
+            };
+            let (changes, skipped) = template_changes(&vcards, &template)?;
+            if !skipped.is_empty() {
+                println!(
+                    "{} contacts skipped, the properties of the template are missing.",
+                    skipped.len()
+                );
+            }
+            changes
+        }
+    };
+    if changes.is_empty() {
+        println!("Nothing to change.");
+        return Ok(());
+    }
+    println!("{}", changes.diff(APP_SHORTNAME)?);
+    if !Confirm::new(&format!("Keep these {} changes ?", changes.len()))
+        .with_default(true)
+        .prompt()?
+    {
+        return Ok(());
+    }
+    for change in changes.changes() {
+        apply_in_memory(books, change)?;
+        stage(change.to_owned());
+    }
+    Ok(())
+}
+
+/// select a book which can receive contacts, other than the present one.
+fn select_destination(books: &[Book], present: &str) -> Result<Option<String>> {
+    let mut destinations = Vec::new();
+    for book in books.iter().filter(|b| b.name != present) {
+        if !is_book_read_only(APP_SHORTNAME, &book.name)? {
+            destinations.push(book.name.to_owned());
+        }
+    }
+    if destinations.is_empty() {
+        println!("No other book can receive contacts.");
+        return Ok(None);
+    }
+    Ok(Select::new("Destination book:", destinations).prompt_skippable()?)
+}
+
+/// ask tags separated by commas.
+fn input_tags() -> Result<Option<Vec<String>>> {
+    let Some(tags) = Text::new("Tags, separated by commas:").prompt_skippable()? else {
+        return Ok(None);
+    };
+    let tags: Vec<String> = tags
+        .split(',')
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+        .collect();
+    Ok((!tags.is_empty()).then_some(tags))
+}
+
+/// ask a template like "EMAIL;TYPE=work:{N.given|lower}@corp.com".
+fn input_template() -> Result<Option<Template>> {
+    let validator = |input: &str| match Template::parse(input) {
+        Ok(_) => Ok(Validation::Valid),
+        Err(e) => Ok(Validation::Invalid(e.to_string().into())),
+    };
+    let Some(template) =
+        Text::new("Template of the property, like EMAIL:{N.given|lower}@corp.com:")
+            .with_validator(validator)
+            .prompt_skippable()?
+    else {
+        return Ok(None);
+    };
+    Ok(Some(Template::parse(&template)?))
+}
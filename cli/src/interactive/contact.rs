@@ -1,15 +1,16 @@
 use core::panic;
 
 use crate::interactive::menu::menu_properties;
-use crate::APP_SHORTNAME;
+use crate::interactive::stage;
+use crate::{store, APP_SHORTNAME};
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 use contact_manager_lib::{
-    add_to_book,
-    api_tools::generate_uid_property,
-    create_contact,
+    access::is_book_read_only,
+    changes::Change,
+    new_vcard,
     paths::books_names,
-    remove_from_book,
+    uuid::Uuid,
     vcard::uuids_from_vcards,
     vcard_parser::{
         constants::PropertyName,
@@ -19,7 +20,7 @@ use contact_manager_lib::{
     vcards_from_book,
 };
 
-use inquire::{Select, Text};
+use inquire::{validator::Validation, MultiSelect, Select, Text};
 
 use promptable::derive_more::{Deref, DerefMut};
 use promptable::promptable_derive::Promptable;
@@ -27,7 +28,7 @@ use promptable::promptable_derive::Promptable;
 #[prompt(custom_prompt_display)]
 #[prompt(params = "book: &str")]
 #[prompt(
-    trigger_del = "remove_from_book(APP_SHORTNAME, params, &uuids_from_vcards(&deleted.iter().map(|d|&d.0).collect())?)?"
+    trigger_del = "contacts_del(params, &uuids_from_vcards(&deleted.iter().map(|d|&d.0).collect())?)?"
 )]
 pub struct Contact {
     #[promptable(function_add = "contact_add_from_or_create(params)?")]
@@ -70,7 +71,11 @@ fn get_fn(contact: &Contact) -> Property {
 //     }
 // }
 
+/// the changes of the contacts are kept pending, like the modifications of their properties.
 fn contact_add_from_or_create(book: &str) -> Result<Option<WrapperVcard>> {
+    if is_book_read_only(APP_SHORTNAME, book)? {
+        bail!("the book {book} is read-only")
+    }
     let options = vec![
         "Create new contact",
         "Add from another book",
@@ -100,68 +105,121 @@ fn vcards2contacts(vcards: Vec<Vcard>) -> VecContact {
 
 fn moves_in_from_book(book: &str) -> Result<Option<WrapperVcard>> {
     // choose book
-    let mut books = books_names(APP_SHORTNAME)?;
-    books.retain(|c| c != book);
+    let mut books = Vec::new();
+    // the contacts of a read-only book can not be removed from it.
+    for b in books_names(APP_SHORTNAME)? {
+        if b != book && !is_book_read_only(APP_SHORTNAME, &b)? {
+            books.push(b);
+        }
+    }
     if let Some(b) =
         Select::new("Select the book to move in the contact from:", books).prompt_skippable()?
     {
-        let mut contacts = vcards2contacts(vcards_from_book(APP_SHORTNAME, Some(&b))?);
+        let mut contacts = vcards2contacts(vcards_from_book(store(), Some(&b), false)?);
         let contacts_already_present: VecContact =
-            vcards2contacts(vcards_from_book(APP_SHORTNAME, Some(book))?);
+            vcards2contacts(vcards_from_book(store(), Some(book), false)?);
         contacts.retain(|c: &Contact| !contacts_already_present.contains(c));
-        // if we could return a vec for add in Promptable
-        // if let Some(contacts) = MultiSelect::new("Select the contacts to move in and delete from this book", contacts.0).prompt_skippable()? {
-        //     let uuids = uuids_from_vcard(&contacts.iter().map(|c|c.vcard.0).collect());
-        //     add_to_book(APP_SHORTNAME, book, &uuids)?;
-        //     remove_from_book(APP_SHORTNAME, &b, &uuids)?;
-        // }
-        if let Some(contact) = Select::new(
+        if let Some(selected) = MultiSelect::new(
             "Select the contacts to move in and delete from this book",
             contacts.0,
         )
         .prompt_skippable()?
         {
-            let uuids = uuids_from_vcards(&vec![&contact.vcard.0])?;
-            add_to_book(APP_SHORTNAME, book, &uuids)?;
-            remove_from_book(APP_SHORTNAME, &b, &uuids)?;
-            return Ok(Some(contact.vcard));
+            for contact in &selected {
+                let uuid = uuids_from_vcards(&vec![&contact.vcard.0])?[0];
+                stage(Change::AddToBook {
+                    uuid,
+                    book: book.to_string(),
+                });
+                stage(Change::RemoveFromBook {
+                    uuid,
+                    book: b.to_owned(),
+                });
+            }
+            return Ok(first_of_selected(selected));
         }
     }
     Ok(None)
 }
 
+/// Promptable adds one contact at a time, the first one selected.
+/// The others are staged too and shown when the books are loaded again, after leaving the management.
+fn first_of_selected(selected: Vec<Contact>) -> Option<WrapperVcard> {
+    if selected.len() > 1 {
+        println!(
+            "the {} other contacts will be shown in the book when going back to the menu.",
+            selected.len() - 1
+        );
+    }
+    selected.into_iter().next().map(|contact| contact.vcard)
+}
+
 fn copy_contacts_from_book(book: &str) -> Result<Option<WrapperVcard>> {
     // contacts
-    let mut contacts = vcards2contacts(vcards_from_book(APP_SHORTNAME, None)?);
+    let mut contacts = vcards2contacts(vcards_from_book(store(), None, false)?);
 
-    let contacts_already_present = vcards2contacts(vcards_from_book(APP_SHORTNAME, Some(book))?);
+    let contacts_already_present = vcards2contacts(vcards_from_book(store(), Some(book), false)?);
     contacts.retain(|c| !contacts_already_present.contains(c));
-    // if we could return a vec for add in Promptable
-    //     if let Some(vcards) = MultiSelect::new("Select the contacts to add", contacts.0).prompt_skippable()? {
-    //         let uuids = uuids_from_vcard(&vcards.iter().map(|c|c.vcard.0).collect());
-    //         add_to_book(APP_SHORTNAME, book, &uuids)?;
-    // }
-
-    if let Some(contact) =
-        Select::new("Select the contacts to add", contacts.0).prompt_skippable()?
+    if let Some(selected) =
+        MultiSelect::new("Select the contacts to add", contacts.0).prompt_skippable()?
     {
-        let uuids = uuids_from_vcards(&vec![&contact.vcard.0])?;
-        add_to_book(APP_SHORTNAME, book, &uuids)?;
-        return Ok(Some(contact.vcard));
+        for contact in &selected {
+            let uuid = uuids_from_vcards(&vec![&contact.vcard.0])?[0];
+            stage(Change::AddToBook {
+                uuid,
+                book: book.to_string(),
+            });
+        }
+        return Ok(first_of_selected(selected));
     }
     Ok(None)
 }
 
 fn contact_new_by_prompt(book: &str) -> Result<Option<WrapperVcard>> {
-    if let Some(fullname) = Text::new("Insert Full Name:").prompt_skippable()? {
-        let mut vcard = Vcard::new(&fullname);
-        vcard.set_property(&generate_uid_property()?.0)?;
-        create_contact(APP_SHORTNAME, &book, &vec![fullname])?;
+    let fullnames: Vec<String> = vcards_from_book(store(), None, false)?
+        .iter()
+        .filter_map(|v| v.get_property_by_name(PropertyName::FN))
+        .map(|p| p.get_value().to_string())
+        .collect();
+    // You can't have two contacts with the same fullname.
+    let validator = move |input: &str| {
+        if fullnames.iter().any(|f| f == input) {
+            Ok(Validation::Invalid("a contact has already this full name".into()))
+        } else {
+            Ok(Validation::Valid)
+        }
+    };
+    if let Some(fullname) = Text::new("Insert Full Name:")
+        .with_validator(validator)
+        .prompt_skippable()?
+    {
+        // the contact shown has the uid it will be created with.
+        let uuid = Uuid::new_v4();
+        let vcard = new_vcard(APP_SHORTNAME, book, &fullname, &uuid)?;
+        stage(Change::CreateContact {
+            uuid,
+            fullname,
+            book: book.to_string(),
+        });
         Ok(Some(WrapperVcard(vcard)))
     } else {
         Ok(None)
     }
 }
+
+/// the contacts deleted are removed from the book when the pending changes are applied.
+fn contacts_del(book: &str, uuids: &[Uuid]) -> Result<()> {
+    if is_book_read_only(APP_SHORTNAME, book)? {
+        bail!("the book {book} is read-only")
+    }
+    for uuid in uuids {
+        stage(Change::RemoveFromBook {
+            uuid: *uuid,
+            book: book.to_string(),
+        });
+    }
+    Ok(())
+}
 fn contact_modify_by_prompt(field: &mut WrapperVcard) -> Result<()> {
     let mut contact = Contact {
         vcard: field.clone(),
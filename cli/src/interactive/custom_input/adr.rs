@@ -1,88 +1,177 @@
 use std::fmt::Display;
 
+use anyhow::Result;
+use contact_manager_lib::address::{join_values, split_values, Address};
 use contact_manager_lib::vcard_parser::traits::HasValue;
 use contact_manager_lib::vcard_parser::vcard::property::property_adr::PropertyAdrData;
-use contact_manager_lib::vcard_parser::vcard::value::value_listcomponent::ValueListComponentData;
 use contact_manager_lib::vcard_parser::vcard::value::Value;
 use promptable::promptable_derive::Promptable;
-// use promptable::Promptable;
 
+use super::to_some_string;
+
+/// help of the questions of the components which can contain a comma.
+#[cfg(feature = "gazetteer")]
+const VALUES_HELP: &str = "several values are separated by commas, write \\, for a comma inside a value";
+
+/// the components of an address to prompt, several values of a component are separated by commas.
+/// A comma inside a value is written "\,", as in the vcard, the questions remind it.
+/// The address is read and written with [Address], so the components not modified are kept as they were.
 #[derive(Promptable, Clone, Default)]
 pub struct Adress {
+    #[promptable(name = "Post office box (\\, for a comma)")]
     #[promptable(default)]
     post_office_box: Option<String>,
+    #[promptable(name = "Apartment or suite (\\, for a comma)")]
     #[promptable(default)]
-    suite: Option<u32>,
-    #[promptable(name = "Number and Adress Street")]
+    suite: Option<String>,
+    #[promptable(name = "Number and Adress Street (\\, for a comma)")]
     #[promptable(short_display)]
     street_adress: String,
-    #[promptable(name = "City")]
+    #[promptable(name = "City (\\, for a comma)")]
     locality: String,
-    #[promptable(name = "State")]
+    #[promptable(name = "State (\\, for a comma)")]
     #[promptable(default)]
     region: Option<String>,
-    postalcode: u32,
+    // a string, postal codes can start with zero or contain letters.
+    postalcode: String,
     #[promptable(default)]
     country: Option<String>,
 }
 impl Display for Adress {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        // if suite is None, display an empty string instead of 0.
-        let suite = self.suite.unwrap_or_default();
-        let suite_display = if suite == 0 {
-            String::new()
-        } else {
-            format!("{suite}")
-        };
-        write!(
-            f,
-            "{};{};{};{};{};{};{}",
-            self.post_office_box.as_ref().unwrap_or(&String::new()),
-            suite_display,
-            self.street_adress,
-            self.locality,
-            self.region.as_ref().unwrap_or(&String::new()),
-            self.postalcode,
-            self.country.as_ref().unwrap_or(&String::new())
-        )
+        write!(f, "{}", self.to_address().to_value())
     }
 }
 
 impl Adress {
     pub fn from_vcard_property(p: &PropertyAdrData) -> Adress {
-        let data = match p.get_value() {
-            Value::ValueListComponent(c) => c,
-            _=> panic!("should not panic because PropertyAdrData Value is ValueListComponent and nothing else")
-        };
-
-        let delim: String = data.delimiter_child.into();
-        let values = data
-            .value
-            .iter()
-            .map(|v| v.join(&delim))
-            .collect::<Vec<String>>();
+        Adress::from_address(&Address::from_value(&p.get_value().to_string()))
+    }
+    pub fn from_address(address: &Address) -> Adress {
         Adress {
-            post_office_box: to_some_string(&values[0]),
-            suite: to_some_u32(&values[1]),
-            street_adress: values[2].to_string(),
-            locality: values[3].to_string(),
-            region: to_some_string(&values[4]),
-            postalcode: values[5].parse().unwrap_or_default(),
-            country: to_some_string(&values[6]),
+            post_office_box: to_some_string(&join_values(&address.post_office_box)),
+            suite: to_some_string(&join_values(&address.extended)),
+            street_adress: join_values(&address.street),
+            locality: join_values(&address.locality),
+            region: to_some_string(&join_values(&address.region)),
+            postalcode: join_values(&address.postal_code),
+            country: to_some_string(&join_values(&address.country)),
+        }
+    }
+    pub fn to_address(&self) -> Address {
+        let values = |text: &Option<String>| split_values(text.as_deref().unwrap_or_default());
+        Address {
+            post_office_box: values(&self.post_office_box),
+            extended: values(&self.suite),
+            street: split_values(&self.street_adress),
+            locality: split_values(&self.locality),
+            region: values(&self.region),
+            postal_code: split_values(&self.postalcode),
+            country: values(&self.country),
         }
     }
-    pub fn to_property_value(&self) -> Value {
-        Value::ValueListComponent(ValueListComponentData::try_from((
-            self.to_string().as_str(),
-            ';',
-            ',',
-        )).expect("should not panic here because Adress to string should be a correct string t obe converted to PropertyAdrData"))
+    pub fn to_property_value(&self) -> Result<Value> {
+        Ok(self.to_address().to_property_value()?)
     }
 }
 
-fn to_some_u32(str: &str) -> Option<u32> {
-    (!str.is_empty()).then(|| str.parse().unwrap_or_default())
-}
-fn to_some_string(str: &str) -> Option<String> {
-    (!str.is_empty()).then(|| str.to_string())
+/// ask the address with suggestions from the gazetteer: the locality from the postal code, or the postal code from the locality.
+/// The postal code must have the format of the country, when it is known.
+#[cfg(feature = "gazetteer")]
+pub fn prompt_with_gazetteer(present: Option<&Adress>) -> Result<Option<Adress>> {
+    use super::gazetteer::{
+        country_code, postal_codes_of, suggest_localities, suggest_postal_codes,
+        valid_postal_code,
+    };
+    use inquire::{validator::Validation, Select, Text};
+
+    let present = present.cloned().unwrap_or_default();
+    let Some(country) = Text::new("Country:")
+        .with_default(present.country.as_deref().unwrap_or_default())
+        .prompt_skippable()?
+    else {
+        return Ok(None);
+    };
+    let code = country_code(&country);
+    let code_validator = code.clone();
+    let code_suggestor = code.clone();
+    let validator = move |input: &str| {
+        if input.trim().is_empty() || valid_postal_code(code_validator.as_deref(), input) {
+            Ok(Validation::Valid)
+        } else {
+            Ok(Validation::Invalid(
+                "this postal code does not have the format of the country".into(),
+            ))
+        }
+    };
+    let suggestor = move |input: &str| Ok(suggest_postal_codes(code_suggestor.as_deref(), input));
+    let Some(postalcode) = Text::new("Postal code, empty to find it from the city:")
+        .with_default(&present.postalcode)
+        .with_validator(validator)
+        .with_autocomplete(suggestor)
+        .prompt_skippable()?
+    else {
+        return Ok(None);
+    };
+    let postalcode = postalcode.trim().to_string();
+    let code_suggestor = code.clone();
+    let postalcode_suggestor = postalcode.clone();
+    let suggestor = move |input: &str| {
+        Ok(suggest_localities(
+            code_suggestor.as_deref(),
+            &postalcode_suggestor,
+            input,
+        ))
+    };
+    // the only locality of the postal code is proposed.
+    let localities = suggest_localities(code.as_deref(), &postalcode, "");
+    let default_locality = match localities.as_slice() {
+        [locality] if present.locality.is_empty() => locality.to_owned(),
+        _ => present.locality.to_owned(),
+    };
+    let Some(locality) = Text::new("City:")
+        .with_default(&default_locality)
+        .with_help_message(VALUES_HELP)
+        .with_autocomplete(suggestor)
+        .prompt_skippable()?
+    else {
+        return Ok(None);
+    };
+    let postalcode = if postalcode.is_empty() {
+        match postal_codes_of(code.as_deref(), &locality).as_slice() {
+            [] => String::new(),
+            [postalcode] => postalcode.to_owned(),
+            codes => Select::new("Postal code:", codes.to_vec())
+                .prompt_skippable()?
+                .unwrap_or_default(),
+        }
+    } else {
+        postalcode
+    };
+    let Some(street_adress) = Text::new("Number and Adress Street:")
+        .with_default(&present.street_adress)
+        .with_help_message(VALUES_HELP)
+        .prompt_skippable()?
+    else {
+        return Ok(None);
+    };
+    let optional = |message: &str, present: &Option<String>| -> Result<Option<String>> {
+        Ok(Text::new(message)
+            .with_default(present.as_deref().unwrap_or_default())
+            .with_help_message(VALUES_HELP)
+            .prompt_skippable()?
+            .and_then(|answer| to_some_string(answer.trim())))
+    };
+    let region = optional("State:", &present.region)?;
+    let post_office_box = optional("Post office box:", &present.post_office_box)?;
+    let suite = optional("Apartment or suite:", &present.suite)?;
+    Ok(Some(Adress {
+        post_office_box,
+        suite,
+        street_adress,
+        locality,
+        region,
+        postalcode,
+        country: to_some_string(country.trim()),
+    }))
 }
@@ -0,0 +1,195 @@
+use std::fmt::Display;
+
+use anyhow::Result;
+use contact_manager_lib::chrono::NaiveDate;
+use inquire::{validator::Validation, Select, Text};
+
+/// names of the months, to pick one.
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+/// a date of BDAY or ANNIVERSARY, where the year, the month or the day can be unknown.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PartialDate {
+    year: Option<i32>,
+    month: Option<u32>,
+    day: Option<u32>,
+}
+
+/// written in the forms of vCard 4: "19850412", "1985-04", "1985", "--0412", "--04" or "---12".
+impl Display for PartialDate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.year, self.month, self.day) {
+            (Some(y), Some(m), Some(d)) => write!(f, "{y:04}{m:02}{d:02}"),
+            (Some(y), Some(m), None) => write!(f, "{y:04}-{m:02}"),
+            // a year with a day but without month can't be written, the day is dropped.
+            (Some(y), None, _) => write!(f, "{y:04}"),
+            (None, Some(m), Some(d)) => write!(f, "--{m:02}{d:02}"),
+            (None, Some(m), None) => write!(f, "--{m:02}"),
+            (None, None, Some(d)) => write!(f, "---{d:02}"),
+            (None, None, None) => Ok(()),
+        }
+    }
+}
+
+impl PartialDate {
+    /// read a date of vCard 4 or vCard 3 ("1985-04-12"), the time is ignored.
+    pub fn from_value(value: &str) -> PartialDate {
+        let date = value.split('T').next().unwrap_or_default();
+        let number = |s: &str| s.parse().ok();
+        if let Some(rest) = date.strip_prefix("---") {
+            return PartialDate {
+                day: number(rest),
+                ..Default::default()
+            };
+        }
+        if let Some(rest) = date.strip_prefix("--") {
+            return PartialDate {
+                month: rest.get(0..2).and_then(number),
+                day: rest.get(2..4).and_then(number),
+                ..Default::default()
+            };
+        }
+        let digits = date.replace('-', "");
+        match date.len() {
+            // "1985-04".
+            7 => PartialDate {
+                year: date.get(0..4).and_then(number),
+                month: date.get(5..7).and_then(number),
+                day: None,
+            },
+            _ => PartialDate {
+                year: digits.get(0..4).and_then(number),
+                month: digits.get(4..6).and_then(number),
+                day: digits.get(6..8).and_then(number),
+            },
+        }
+    }
+}
+
+/// ask the year, the month and the day, each one can be left unknown.
+pub fn prompt_date(present: Option<PartialDate>) -> Result<Option<PartialDate>> {
+    let present = present.unwrap_or_default();
+    let Some(year) = prompt_number("Year, empty if unknown:", present.year, |y| {
+        (0..=9999).contains(&y)
+    })?
+    else {
+        return Ok(None);
+    };
+    let mut months = vec!["Unknown"];
+    months.extend(MONTHS);
+    let Some(month) = Select::new("Month:", months)
+        .with_starting_cursor(present.month.unwrap_or_default() as usize)
+        .raw_prompt_skippable()?
+    else {
+        return Ok(None);
+    };
+    let month = (month.index > 0).then_some(month.index as u32);
+    // without month, a day can only be given without year.
+    if month.is_none() && year.is_some() {
+        return Ok(Some(PartialDate {
+            year,
+            month,
+            day: None,
+        }));
+    }
+    let Some(day) = prompt_number(
+        "Day, empty if unknown:",
+        present.day.map(|d| d as i32),
+        move |d| match month {
+            // 2000 is a leap year, so the 29th of February is valid without year.
+            Some(m) => NaiveDate::from_ymd_opt(year.unwrap_or(2000), m, d as u32).is_some(),
+            None => (1..=31).contains(&d),
+        },
+    )?
+    else {
+        return Ok(None);
+    };
+    Ok(Some(PartialDate {
+        year,
+        month,
+        day: day.map(|d| d as u32),
+    }))
+}
+
+/// ask a number that can be left empty. None if the question is skipped.
+fn prompt_number(
+    message: &str,
+    present: Option<i32>,
+    valid: impl Fn(i32) -> bool + Clone + 'static,
+) -> Result<Option<Option<i32>>> {
+    let validator = move |input: &str| {
+        if input.trim().is_empty() || input.trim().parse().is_ok_and(valid.clone()) {
+            Ok(Validation::Valid)
+        } else {
+            Ok(Validation::Invalid("this number is not valid here".into()))
+        }
+    };
+    let present = present.map(|n| n.to_string()).unwrap_or_default();
+    Ok(Text::new(message)
+        .with_initial_value(&present)
+        .with_validator(validator)
+        .prompt_skippable()?
+        .map(|answer| answer.trim().parse().ok()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: Option<i32>, month: Option<u32>, day: Option<u32>) -> PartialDate {
+        PartialDate { year, month, day }
+    }
+
+    #[test]
+    fn date_from_value() {
+        let full = date(Some(1985), Some(4), Some(12));
+        assert_eq!(PartialDate::from_value("19850412"), full);
+        // vCard 3 and a date with a time.
+        assert_eq!(PartialDate::from_value("1985-04-12"), full);
+        assert_eq!(PartialDate::from_value("19850412T101500Z"), full);
+        assert_eq!(
+            PartialDate::from_value("1985-04"),
+            date(Some(1985), Some(4), None)
+        );
+        assert_eq!(
+            PartialDate::from_value("1985"),
+            date(Some(1985), None, None)
+        );
+        assert_eq!(
+            PartialDate::from_value("--0412"),
+            date(None, Some(4), Some(12))
+        );
+        assert_eq!(PartialDate::from_value("--04"), date(None, Some(4), None));
+        assert_eq!(PartialDate::from_value("---12"), date(None, None, Some(12)));
+        // a text value which is not a date, with a character of several bytes.
+        assert_eq!(PartialDate::from_value("123é04"), date(None, Some(4), None));
+        assert_eq!(PartialDate::from_value("é1985"), PartialDate::default());
+    }
+
+    #[test]
+    fn date_display() {
+        for value in ["19850412", "1985-04", "1985", "--0412", "--04", "---12"] {
+            assert_eq!(PartialDate::from_value(value).to_string(), value);
+        }
+        assert_eq!(
+            PartialDate::from_value("1985-04-12").to_string(),
+            "19850412"
+        );
+        // the day can't be written without month when the year is known.
+        assert_eq!(date(Some(1985), None, Some(12)).to_string(), "1985");
+        assert_eq!(PartialDate::default().to_string(), "");
+    }
+}
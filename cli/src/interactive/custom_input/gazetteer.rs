@@ -0,0 +1,202 @@
+use std::{fs, path::PathBuf, sync::OnceLock};
+
+use anyhow::Result;
+use contact_manager_lib::{paths::data_directory, regex::Regex};
+
+use crate::APP_SHORTNAME;
+
+/// name of the file of postal codes, in the data directory of the application.
+/// Each line is a country code, a postal code and a locality, separated by tabulations, commas or semicolons.
+/// The files of postal codes of GeoNames can be used as is.
+pub const GAZETTEER_FILE: &str = "gazetteer.csv";
+
+/// format of the postal codes of some countries: ISO code, name and regular expression.
+const POSTAL_FORMATS: [(&str, &str, &str); 16] = [
+    ("AT", "Austria", r"^\d{4}$"),
+    ("BE", "Belgium", r"^\d{4}$"),
+    ("BR", "Brazil", r"^\d{5}-?\d{3}$"),
+    ("CA", "Canada", r"^[A-Z]\d[A-Z] ?\d[A-Z]\d$"),
+    ("CH", "Switzerland", r"^\d{4}$"),
+    ("DE", "Germany", r"^\d{5}$"),
+    ("DK", "Denmark", r"^\d{4}$"),
+    ("ES", "Spain", r"^\d{5}$"),
+    ("FR", "France", r"^\d{5}$"),
+    ("GB", "United Kingdom", r"^[A-Z]{1,2}\d[A-Z\d]? ?\d[A-Z]{2}$"),
+    ("IT", "Italy", r"^\d{5}$"),
+    ("JP", "Japan", r"^\d{3}-?\d{4}$"),
+    ("LU", "Luxembourg", r"^(L-)?\d{4}$"),
+    ("NL", "Netherlands", r"^\d{4} ?[A-Z]{2}$"),
+    ("PT", "Portugal", r"^\d{4}-\d{3}$"),
+    ("US", "United States", r"^\d{5}(-\d{4})?$"),
+];
+
+/// maximum number of suggestions shown while typing.
+const SUGGESTIONS_MAX: usize = 15;
+
+/// a postal code with its locality.
+#[derive(Clone)]
+pub struct Place {
+    country: String,
+    postal_code: String,
+    locality: String,
+}
+
+/// places read from the file of postal codes, once.
+static GAZETTEER: OnceLock<Vec<Place>> = OnceLock::new();
+/// formats of the postal codes, compiled once.
+static POSTAL_REGEXES: OnceLock<Vec<(&str, Regex)>> = OnceLock::new();
+
+fn gazetteer_path() -> Result<PathBuf> {
+    Ok(data_directory(APP_SHORTNAME)?.join(GAZETTEER_FILE))
+}
+
+/// the places of the file of postal codes, empty if there is no file.
+pub fn places() -> &'static [Place] {
+    GAZETTEER.get_or_init(|| {
+        let Some(content) = gazetteer_path()
+            .ok()
+            .and_then(|path| fs::read_to_string(path).ok())
+        else {
+            return Vec::new();
+        };
+        content.lines().filter_map(read_place).collect()
+    })
+}
+
+fn read_place(line: &str) -> Option<Place> {
+    let separator = ['\t', ';', ',']
+        .into_iter()
+        .find(|s| line.contains(*s))?;
+    let mut columns = line.split(separator).map(|c| c.trim().trim_matches('"'));
+    let place = Place {
+        country: columns.next()?.to_uppercase(),
+        postal_code: columns.next()?.to_string(),
+        locality: columns.next()?.to_string(),
+    };
+    // the line of the titles, or an incomplete line.
+    (place.country.len() == 2 && !place.postal_code.is_empty() && !place.locality.is_empty())
+        .then_some(place)
+}
+
+/// the ISO code of a country given by its code or its name, if it is known.
+pub fn country_code(country: &str) -> Option<String> {
+    let country = country.trim();
+    if let Some((code, _, _)) = POSTAL_FORMATS
+        .iter()
+        .find(|(code, name, _)| code.eq_ignore_ascii_case(country) || name.eq_ignore_ascii_case(country))
+    {
+        return Some(code.to_string());
+    }
+    let code = country.to_uppercase();
+    places().iter().any(|p| p.country == code).then_some(code)
+}
+
+/// true if the postal code has the format of the country, or if the format of the country is unknown.
+pub fn valid_postal_code(country: Option<&str>, postal_code: &str) -> bool {
+    let regexes = POSTAL_REGEXES.get_or_init(|| {
+        POSTAL_FORMATS
+            .iter()
+            .map(|(code, _, format)| {
+                (
+                    *code,
+                    Regex::new(format).expect("the formats of postal codes are valid"),
+                )
+            })
+            .collect()
+    });
+    let Some((_, format)) = country.and_then(|country| {
+        regexes
+            .iter()
+            .find(|(code, _)| code.eq_ignore_ascii_case(country))
+    }) else {
+        return true;
+    };
+    format.is_match(&postal_code.trim().to_uppercase())
+}
+
+fn places_of(country: Option<&str>) -> impl Iterator<Item = &'static Place> + '_ {
+    places()
+        .iter()
+        .filter(move |p| country.is_none_or(|c| p.country == c))
+}
+
+/// postal codes starting with the input, for the country if known.
+pub fn suggest_postal_codes(country: Option<&str>, input: &str) -> Vec<String> {
+    let mut codes: Vec<String> = places_of(country)
+        .filter(|p| p.postal_code.starts_with(input.trim()))
+        .map(|p| p.postal_code.to_owned())
+        .collect();
+    codes.sort();
+    codes.dedup();
+    codes.truncate(SUGGESTIONS_MAX);
+    codes
+}
+
+/// localities of the postal code, or containing the input if there is no postal code.
+pub fn suggest_localities(country: Option<&str>, postal_code: &str, input: &str) -> Vec<String> {
+    let input = input.trim().to_lowercase();
+    let mut localities: Vec<String> = places_of(country)
+        .filter(|p| postal_code.is_empty() || p.postal_code == postal_code)
+        .filter(|p| p.locality.to_lowercase().contains(&input))
+        .map(|p| p.locality.to_owned())
+        .collect();
+    localities.sort();
+    localities.dedup();
+    localities.truncate(SUGGESTIONS_MAX);
+    localities
+}
+
+/// postal codes of a locality, for the country if known.
+pub fn postal_codes_of(country: Option<&str>, locality: &str) -> Vec<String> {
+    let mut codes: Vec<String> = places_of(country)
+        .filter(|p| p.locality.eq_ignore_ascii_case(locality.trim()))
+        .map(|p| p.postal_code.to_owned())
+        .collect();
+    codes.sort();
+    codes.dedup();
+    codes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn place_read() {
+        // a line of GeoNames, separated by tabulations.
+        let place = read_place("FR\t21000\tDijon\tBourgogne-Franche-Comté").unwrap();
+        assert_eq!(
+            (place.country.as_str(), place.postal_code.as_str(), place.locality.as_str()),
+            ("FR", "21000", "Dijon")
+        );
+        let place = read_place("\"be\";\"1000\";\"Bruxelles\"").unwrap();
+        assert_eq!(place.country, "BE");
+        assert_eq!(place.locality, "Bruxelles");
+        assert!(read_place("GB,SW1A 1AA,London").is_some());
+        // the line of the titles and the incomplete lines.
+        assert!(read_place("country,postal code,locality").is_none());
+        assert!(read_place("FR,21000").is_none());
+        assert!(read_place("FR,,Dijon").is_none());
+        assert!(read_place("FR 21000 Dijon").is_none());
+    }
+
+    #[test]
+    fn code_of_country() {
+        assert_eq!(country_code("fr").as_deref(), Some("FR"));
+        assert_eq!(country_code(" France ").as_deref(), Some("FR"));
+        assert_eq!(country_code("united kingdom").as_deref(), Some("GB"));
+    }
+
+    #[test]
+    fn postal_code_valid() {
+        assert!(valid_postal_code(Some("FR"), "21000"));
+        assert!(!valid_postal_code(Some("FR"), "2100"));
+        assert!(valid_postal_code(Some("gb"), "sw1a 1aa"));
+        assert!(valid_postal_code(Some("CA"), "K1A 0B1"));
+        assert!(!valid_postal_code(Some("CA"), "12345"));
+        assert!(valid_postal_code(Some("US"), "12345-6789"));
+        // the format of the country is unknown.
+        assert!(valid_postal_code(Some("ZZ"), "anything"));
+        assert!(valid_postal_code(None, "anything"));
+    }
+}
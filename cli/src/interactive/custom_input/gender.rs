@@ -0,0 +1,70 @@
+use std::fmt::Display;
+
+use anyhow::Result;
+use inquire::Select;
+use promptable::promptable_derive::Promptable;
+
+use super::{escape_component, split_components, to_some_string};
+
+/// letters of the sex component of GENDER, with their meaning.
+const SEXES: [(&str, &str); 6] = [
+    ("", "not given"),
+    ("M", "male"),
+    ("F", "female"),
+    ("O", "other"),
+    ("N", "none or not applicable"),
+    ("U", "unknown"),
+];
+
+/// components of the property GENDER.
+#[derive(Promptable, Clone, Default)]
+pub struct Gender {
+    #[promptable(function_new = "prompt_sex(\"\")?")]
+    #[promptable(function_mod = "modify_sex(field)?")]
+    sex: String,
+    #[promptable(name = "Gender identity")]
+    #[promptable(default)]
+    identity: Option<String>,
+}
+
+impl Display for Gender {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.identity {
+            Some(identity) => write!(f, "{};{}", self.sex, escape_component(identity)),
+            None => write!(f, "{}", self.sex),
+        }
+    }
+}
+
+impl Gender {
+    pub fn from_value(value: &str) -> Gender {
+        let mut values = split_components(value);
+        values.resize(2, String::new());
+        Gender {
+            sex: values[0].to_uppercase(),
+            identity: to_some_string(&values[1]),
+        }
+    }
+}
+
+fn prompt_sex(present: &str) -> Result<Option<String>> {
+    let options: Vec<String> = SEXES
+        .iter()
+        .map(|(letter, meaning)| format!("{letter} {meaning}").trim().to_string())
+        .collect();
+    let cursor = SEXES
+        .iter()
+        .position(|(letter, _)| *letter == present)
+        .unwrap_or_default();
+    Ok(Select::new("Sex:", options)
+        .with_starting_cursor(cursor)
+        .raw_prompt_skippable()?
+        .map(|choice| SEXES[choice.index].0.to_string()))
+}
+
+fn modify_sex(field: &mut String) -> Result<()> {
+    if let Some(sex) = prompt_sex(field)? {
+        *field = sex;
+    }
+    Ok(())
+}
@@ -0,0 +1,61 @@
+use std::fmt::Display;
+
+use anyhow::Result;
+use inquire::{validator::Validation, CustomType};
+use promptable::promptable_derive::Promptable;
+
+/// position of the property GEO, written as a geo URI.
+#[derive(Promptable, Clone, Default)]
+pub struct Geo {
+    #[promptable(function_new = "prompt_coordinate(\"Latitude:\", 90.0, None)?")]
+    #[promptable(function_mod = "modify_coordinate(\"Latitude:\", 90.0, field)?")]
+    latitude: f64,
+    #[promptable(function_new = "prompt_coordinate(\"Longitude:\", 180.0, None)?")]
+    #[promptable(function_mod = "modify_coordinate(\"Longitude:\", 180.0, field)?")]
+    longitude: f64,
+}
+
+impl Display for Geo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "geo:{},{}", self.latitude, self.longitude)
+    }
+}
+
+impl Geo {
+    /// read a geo URI, the coordinates are zero if it can't be read.
+    pub fn from_value(value: &str) -> Geo {
+        let coordinates = value.trim_start_matches("geo:");
+        // the uncertainty or the coordinate reference system can follow.
+        let coordinates = coordinates.split(';').next().unwrap_or_default();
+        let mut coordinates = coordinates.split(',').map(|c| c.parse().unwrap_or_default());
+        Geo {
+            latitude: coordinates.next().unwrap_or_default(),
+            longitude: coordinates.next().unwrap_or_default(),
+        }
+    }
+}
+
+/// ask a coordinate between -max and max.
+fn prompt_coordinate(message: &str, max: f64, present: Option<f64>) -> Result<Option<f64>> {
+    let validator = move |input: &f64| {
+        if input.abs() <= max {
+            Ok(Validation::Valid)
+        } else {
+            Ok(Validation::Invalid(
+                format!("must be between -{max} and {max}").into(),
+            ))
+        }
+    };
+    let mut prompt = CustomType::<f64>::new(message).with_validator(validator);
+    if let Some(present) = present {
+        prompt = prompt.with_default(present);
+    }
+    Ok(prompt.prompt_skippable()?)
+}
+
+fn modify_coordinate(message: &str, max: f64, field: &mut f64) -> Result<()> {
+    if let Some(coordinate) = prompt_coordinate(message, max, Some(*field))? {
+        *field = coordinate;
+    }
+    Ok(())
+}
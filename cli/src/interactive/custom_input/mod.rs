@@ -1,16 +1,45 @@
 pub mod adr;
+pub mod date;
+#[cfg(feature = "gazetteer")]
+pub mod gazetteer;
+pub mod gender;
+pub mod geo;
+pub mod name;
+pub mod org;
+pub mod param;
+pub mod tz;
 use crate::interactive::custom_input::adr::Adress;
+use crate::interactive::custom_input::date::{prompt_date, PartialDate};
+use crate::interactive::custom_input::gender::Gender;
+use crate::interactive::custom_input::geo::Geo;
+use crate::interactive::custom_input::name::Name;
+use crate::interactive::custom_input::org::Organization;
+use crate::interactive::custom_input::tz::TimeZone;
 use crate::interactive::show_property;
 use crate::interactive::Property;
 use anyhow::bail;
 use anyhow::Result;
 use contact_manager_lib::vcard_parser::constants::PropertyName;
 use contact_manager_lib::vcard_parser::traits::HasValue;
-use contact_manager_lib::vcard_parser::vcard::value::value_listcomponent::ValueListComponentData;
 use contact_manager_lib::vcard_parser::vcard::value::Value;
 use inquire::validator::Validation;
 use inquire::Text;
 use promptable::basics::promptable::Promptable;
+/// ask the value with the guided editor, modifying the present one if there is a property.
+macro_rules! guided {
+    ($editor:ty, $name:expr, $property:expr) => {{
+        let value = match $property.as_ref() {
+            Some(p) => {
+                let mut editor = <$editor>::from_value(&p.get_value().to_string());
+                editor.modify_by_prompt(())?;
+                Some(editor.to_string())
+            }
+            None => <$editor>::new_by_prompt(())?.map(|editor| editor.to_string()),
+        };
+        set_or_return_value($name, $property, value)
+    }};
+}
+
 pub fn input_value(name: &str, property: Option<&mut Property>) -> Result<Option<Value>> {
     // a simple implementation would be to use TryFrom.
     // a more user friendly implementation would be to guide the user and to ask with multiple questions for value with listcomponent. or textlist. Like Adr.
@@ -20,29 +49,48 @@ pub fn input_value(name: &str, property: Option<&mut Property>) -> Result<Option
     let n = name.to_string();
     match name {
         PropertyName::ADR => {
-            // TODO, ask if the user want a selection of the adress to be sure it exist or verify while he writes and ask if he wants to input a unkown adress if that's the case.
-            // the proposed anwser could be filtered by features. For example, if feature adr-fr is enabled, the adresses of France will appear.
-            // if no features about the adresses is enabled, the user will be able to input any adresses.
+            // with the feature gazetteer, the city and the postal code are suggested from a file of postal codes.
+            // without it, the user can input any adress.
             if let Some(p) = property {
                 let data_adr = match p {
                     Property::PropertyAdr(c) => c,
                     _ => bail!("the name was ADR but the property was not"),
                 };
                 let mut adr = Adress::from_vcard_property(&data_adr);
+                #[cfg(feature = "gazetteer")]
+                if let Some(new) = adr::prompt_with_gazetteer(Some(&adr))? {
+                    adr = new;
+                }
+                #[cfg(not(feature = "gazetteer"))]
                 adr.modify_by_prompt(())?;
-                p.set_value(adr.to_property_value())?;
+                p.set_value(adr.to_property_value()?)?;
                 Ok(None)
             } else {
-                let adr = Adress::new_by_prompt(())?.unwrap();
-                let value = Value::try_from(ValueListComponentData::try_from((
-                    adr.to_string().as_str(),
-                    ';',
-                    ',',
-                ))?)?;
-                Ok(Some(value))
+                #[cfg(feature = "gazetteer")]
+                let Some(adr) = adr::prompt_with_gazetteer(None)?
+                else {
+                    return Ok(None);
+                };
+                #[cfg(not(feature = "gazetteer"))]
+                let Some(adr) = Adress::new_by_prompt(())?
+                else {
+                    return Ok(None);
+                };
+                Ok(Some(adr.to_property_value()?))
             }
         }
-
+        PropertyName::N => guided!(Name, name, property),
+        PropertyName::ORG => guided!(Organization, name, property),
+        PropertyName::GEO => guided!(Geo, name, property),
+        PropertyName::TZ => guided!(TimeZone, name, property),
+        PropertyName::GENDER => guided!(Gender, name, property),
+        PropertyName::BDAY | PropertyName::ANNIVERSARY => {
+            let present = property
+                .as_ref()
+                .map(|p| PartialDate::from_value(&p.get_value().to_string()));
+            let value = prompt_date(present)?.map(|date| date.to_string());
+            set_or_return_value(name, property, value)
+        }
         _ => {
             let validator = move |input: &str| {
                 let raw = format!("{n}:{input}\n");
@@ -72,3 +120,70 @@ pub fn input_value(name: &str, property: Option<&mut Property>) -> Result<Option
         }
     }
 }
+
+/// set the raw value to the property if there is one, or return it.
+fn set_or_return_value(
+    name: &str,
+    property: Option<&mut Property>,
+    value: Option<String>,
+) -> Result<Option<Value>> {
+    let Some(value) = value else {
+        return Ok(None);
+    };
+    let value = Property::create_from_str(&format!("{name}:{value}\n"))?
+        .get_value()
+        .to_owned();
+    if let Some(p) = property {
+        p.set_value(value)?;
+        return Ok(None);
+    }
+    Ok(Some(value))
+}
+
+/// split a structured value on the semicolons not escaped, removing the escaping of the semicolons.
+fn split_components(value: &str) -> Vec<String> {
+    let mut components = vec![String::new()];
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some(';') => components.last_mut().expect("never empty").push(';'),
+                Some(next) => {
+                    let last = components.last_mut().expect("never empty");
+                    last.push('\\');
+                    last.push(next);
+                }
+                None => components.last_mut().expect("never empty").push('\\'),
+            },
+            ';' => components.push(String::new()),
+            c => components.last_mut().expect("never empty").push(c),
+        }
+    }
+    components
+}
+
+/// escape the semicolons of a component, so they are not read as separators.
+fn escape_component(component: &str) -> String {
+    component.replace(';', "\\;")
+}
+
+fn to_some_string(str: &str) -> Option<String> {
+    (!str.is_empty()).then(|| str.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn components_split() {
+        assert_eq!(split_components("Dupont;Jean;;;"), ["Dupont", "Jean", "", "", ""]);
+        // an escaped semicolon is in the component, the other escapes are kept.
+        assert_eq!(split_components("ACME\\; Inc;R\\,D"), ["ACME; Inc", "R\\,D"]);
+        assert_eq!(split_components(""), [""]);
+        assert_eq!(
+            split_components(&escape_component("ACME; Inc")),
+            ["ACME; Inc"]
+        );
+    }
+}
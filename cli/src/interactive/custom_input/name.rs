@@ -0,0 +1,51 @@
+use std::fmt::Display;
+
+use promptable::promptable_derive::Promptable;
+
+use super::{escape_component, split_components, to_some_string};
+
+/// components of the property N.
+#[derive(Promptable, Clone, Default)]
+pub struct Name {
+    #[promptable(name = "Family name")]
+    family: String,
+    #[promptable(name = "Given name")]
+    given: String,
+    #[promptable(name = "Additional names")]
+    #[promptable(default)]
+    additional: Option<String>,
+    #[promptable(name = "Honorific prefix")]
+    #[promptable(default)]
+    prefix: Option<String>,
+    #[promptable(name = "Honorific suffix")]
+    #[promptable(default)]
+    suffix: Option<String>,
+}
+
+impl Display for Name {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{};{};{};{};{}",
+            escape_component(&self.family),
+            escape_component(&self.given),
+            escape_component(self.additional.as_deref().unwrap_or_default()),
+            escape_component(self.prefix.as_deref().unwrap_or_default()),
+            escape_component(self.suffix.as_deref().unwrap_or_default())
+        )
+    }
+}
+
+impl Name {
+    pub fn from_value(value: &str) -> Name {
+        let mut values = split_components(value);
+        values.resize(5, String::new());
+        Name {
+            family: values[0].to_string(),
+            given: values[1].to_string(),
+            additional: to_some_string(&values[2]),
+            prefix: to_some_string(&values[3]),
+            suffix: to_some_string(&values[4]),
+        }
+    }
+}
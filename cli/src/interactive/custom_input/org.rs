@@ -0,0 +1,67 @@
+use std::fmt::Display;
+
+use anyhow::Result;
+use inquire::Text;
+use promptable::promptable_derive::Promptable;
+
+use super::{escape_component, split_components};
+
+/// the organization of the property ORG, with its units from the largest to the smallest.
+#[derive(Promptable, Clone, Default)]
+pub struct Organization {
+    #[promptable(name = "Organization")]
+    name: String,
+    #[promptable(function_new = "prompt_units(Vec::new())?")]
+    #[promptable(function_mod = "modify_units(field)?")]
+    units: Vec<String>,
+}
+
+impl Display for Organization {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", escape_component(&self.name))?;
+        for unit in &self.units {
+            write!(f, ";{}", escape_component(unit))?;
+        }
+        Ok(())
+    }
+}
+
+impl Organization {
+    pub fn from_value(value: &str) -> Organization {
+        let mut values = split_components(value).into_iter();
+        Organization {
+            name: values.next().unwrap_or_default(),
+            units: values.filter(|u| !u.is_empty()).collect(),
+        }
+    }
+}
+
+/// ask the units one by one, an empty answer ends the list.
+fn prompt_units(mut units: Vec<String>) -> Result<Option<Vec<String>>> {
+    while let Some(unit) = Text::new("Unit, empty to finish:").prompt_skippable()? {
+        if unit.trim().is_empty() {
+            break;
+        }
+        units.push(unit.trim().to_string());
+    }
+    Ok(Some(units))
+}
+
+/// modify the units one by one, as a unit can contain a comma, then ask the new ones.
+fn modify_units(units: &mut Vec<String>) -> Result<()> {
+    let mut modified = Vec::new();
+    for unit in units.iter() {
+        match Text::new("Unit, empty to remove it:")
+            .with_initial_value(unit)
+            .prompt_skippable()?
+        {
+            Some(answer) if answer.trim().is_empty() => {}
+            Some(answer) => modified.push(answer.trim().to_string()),
+            None => return Ok(()),
+        }
+    }
+    if let Some(modified) = prompt_units(modified)? {
+        *units = modified;
+    }
+    Ok(())
+}
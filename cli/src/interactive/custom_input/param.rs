@@ -0,0 +1,202 @@
+use anyhow::Result;
+use inquire::{validator::Validation, MultiSelect, Select, Text};
+
+use crate::interactive::validator_param;
+
+/// values of TYPE for every property.
+const TYPES_GENERAL: [&str; 2] = ["home", "work"];
+/// values of TYPE for TEL.
+const TYPES_TEL: [&str; 7] = ["cell", "voice", "text", "fax", "video", "pager", "textphone"];
+/// values of TYPE for RELATED, the relations of the contact.
+const TYPES_RELATED: [&str; 20] = [
+    "contact",
+    "acquaintance",
+    "friend",
+    "met",
+    "co-worker",
+    "colleague",
+    "co-resident",
+    "neighbor",
+    "child",
+    "parent",
+    "sibling",
+    "spouse",
+    "kin",
+    "muse",
+    "crush",
+    "date",
+    "sweetheart",
+    "me",
+    "agent",
+    "emergency",
+];
+/// language tags proposed for LANGUAGE, another one can be written.
+const LANGUAGES: [&str; 24] = [
+    "ar", "de", "de-CH", "en", "en-GB", "en-US", "es", "es-MX", "fr", "fr-BE", "fr-CA", "fr-CH",
+    "hi", "it", "ja", "ko", "nl", "pl", "pt", "pt-BR", "ru", "sv", "zh-Hans", "zh-Hant",
+];
+const OTHER: &str = "Other...";
+
+/// ask the value of a parameter with the input fitting it, the present value is proposed.
+/// altids are the ALTID values used by the properties of the same name in the contact.
+/// Return the value as written in the property, None if the user cancels.
+pub fn input_param(
+    property_name: &str,
+    param_name: &str,
+    present: Option<&str>,
+    altids: &[String],
+) -> Result<Option<String>> {
+    let present = present.map(|p| p.trim_matches('"'));
+    match param_name.to_uppercase().as_str() {
+        "TYPE" => input_type(property_name, present),
+        "PREF" => input_pref(present),
+        "LANGUAGE" => input_language(present),
+        "ALTID" => input_altid(present, altids),
+        "LABEL" => input_label(present),
+        _ => {
+            let name = param_name.to_string();
+            let validator = move |input: &str| {
+                let param_raw = [";", &name, "=", input].concat();
+                Ok(validator_param(param_raw)?)
+            };
+            let mut text = Text::new("value of parameter:").with_validator(validator);
+            if let Some(present) = present {
+                text = text.with_default(present);
+            }
+            Ok(text.prompt_skippable()?)
+        }
+    }
+}
+
+/// select the types among the ones valid for the property, keeping the unknown ones present.
+fn input_type(property_name: &str, present: Option<&str>) -> Result<Option<String>> {
+    let mut types: Vec<String> = TYPES_GENERAL.iter().map(|t| t.to_string()).collect();
+    match property_name.to_uppercase().as_str() {
+        "TEL" => types.extend(TYPES_TEL.iter().map(|t| t.to_string())),
+        "RELATED" => types.extend(TYPES_RELATED.iter().map(|t| t.to_string())),
+        _ => {}
+    }
+    let present: Vec<String> = present
+        .unwrap_or_default()
+        .split(',')
+        .map(|t| t.trim().to_lowercase())
+        .filter(|t| !t.is_empty())
+        .collect();
+    for t in &present {
+        if !types.contains(t) {
+            types.push(t.to_owned());
+        }
+    }
+    let defaults: Vec<usize> = types
+        .iter()
+        .enumerate()
+        .filter(|(_, t)| present.contains(t))
+        .map(|(i, _)| i)
+        .collect();
+    let Some(chosen) = MultiSelect::new("Types:", types)
+        .with_default(&defaults)
+        .prompt_skippable()?
+    else {
+        return Ok(None);
+    };
+    if chosen.is_empty() {
+        return Ok(None);
+    }
+    // several values must be quoted, the comma separating them.
+    Ok(Some(if chosen.len() > 1 {
+        format!("\"{}\"", chosen.join(","))
+    } else {
+        chosen.join(",")
+    }))
+}
+
+/// select the preference between 1, the most preferred, and 100.
+fn input_pref(present: Option<&str>) -> Result<Option<String>> {
+    let prefs: Vec<u8> = (1..=100).collect();
+    let cursor = present
+        .and_then(|p| p.parse::<usize>().ok())
+        .filter(|p| (1..=100).contains(p))
+        .map_or(0, |p| p - 1);
+    Ok(Select::new("Preference, 1 is the most preferred:", prefs)
+        .with_starting_cursor(cursor)
+        .without_filtering()
+        .prompt_skippable()?
+        .map(|p| p.to_string()))
+}
+
+/// select a common language tag, or write another one.
+fn input_language(present: Option<&str>) -> Result<Option<String>> {
+    let mut options: Vec<&str> = LANGUAGES.to_vec();
+    options.push(OTHER);
+    let cursor = present
+        .and_then(|p| options.iter().position(|l| l.eq_ignore_ascii_case(p)))
+        .unwrap_or_default();
+    match Select::new("Language:", options)
+        .with_starting_cursor(cursor)
+        .prompt_skippable()?
+    {
+        Some(OTHER) => {
+            let validator = |input: &str| {
+                if valid_language_tag(input) {
+                    Ok(Validation::Valid)
+                } else {
+                    Ok(Validation::Invalid(
+                        "a language tag is like \"fr\" or \"en-US\"".into(),
+                    ))
+                }
+            };
+            Ok(Text::new("Language tag:")
+                .with_default(present.unwrap_or_default())
+                .with_validator(validator)
+                .prompt_skippable()?)
+        }
+        language => Ok(language.map(|l| l.to_string())),
+    }
+}
+
+/// a tag of BCP 47: a language of 2 or 3 letters, then subtags of 1 to 8 letters or digits.
+fn valid_language_tag(tag: &str) -> bool {
+    let mut subtags = tag.split('-');
+    subtags
+        .next()
+        .is_some_and(|l| (2..=3).contains(&l.len()) && l.chars().all(|c| c.is_ascii_alphabetic()))
+        && subtags.all(|s| (1..=8).contains(&s.len()) && s.chars().all(|c| c.is_ascii_alphanumeric()))
+}
+
+/// select the group of alternative representations, among the ones of the contact or a new one.
+fn input_altid(present: Option<&str>, altids: &[String]) -> Result<Option<String>> {
+    let new = (1..)
+        .map(|n: u32| n.to_string())
+        .find(|n| !altids.contains(n))
+        .expect("there is always an unused number");
+    let mut options: Vec<String> = altids.iter().map(|a| format!("group {a}")).collect();
+    options.push(format!("new group {new}"));
+    let cursor = present
+        .and_then(|p| altids.iter().position(|a| a == p))
+        .unwrap_or_default();
+    Ok(Select::new(
+        "Group of the same value in other representations, like other languages:",
+        options,
+    )
+    .with_starting_cursor(cursor)
+    .raw_prompt_skippable()?
+    .map(|choice| altids.get(choice.index).unwrap_or(&new).to_owned()))
+}
+
+/// write the label of an address as it would be printed, on several lines.
+fn input_label(present: Option<&str>) -> Result<Option<String>> {
+    let present = present.unwrap_or_default().replace("\\n", " / ");
+    let Some(label) = Text::new("Label, lines separated by \" / \":")
+        .with_default(&present)
+        .prompt_skippable()?
+    else {
+        return Ok(None);
+    };
+    // a quoted value can't contain double quotes.
+    let label = label
+        .split(" / ")
+        .map(|line| line.trim().replace('"', "'"))
+        .collect::<Vec<String>>()
+        .join("\\n");
+    Ok(Some(format!("\"{label}\"")))
+}
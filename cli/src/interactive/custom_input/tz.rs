@@ -0,0 +1,49 @@
+use std::fmt::Display;
+
+use anyhow::Result;
+use chrono_tz::TZ_VARIANTS;
+use inquire::Select;
+use promptable::promptable_derive::Promptable;
+
+/// time zone of the property TZ, as a name of the IANA database.
+#[derive(Promptable, Clone, Default)]
+pub struct TimeZone {
+    #[promptable(function_new = "prompt_zone(\"\")?")]
+    #[promptable(function_mod = "modify_zone(field)?")]
+    zone: String,
+}
+
+impl Display for TimeZone {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.zone)
+    }
+}
+
+impl TimeZone {
+    pub fn from_value(value: &str) -> TimeZone {
+        TimeZone {
+            zone: value.to_string(),
+        }
+    }
+}
+
+/// select a zone, typing filters the list, example: "paris".
+/// A present value which is not a zone, like the UTC offset "-0500", is offered first to keep it.
+fn prompt_zone(present: &str) -> Result<Option<String>> {
+    let mut zones: Vec<&str> = TZ_VARIANTS.iter().map(|tz| tz.name()).collect();
+    if !present.is_empty() && !zones.contains(&present) {
+        zones.insert(0, present);
+    }
+    let cursor = zones.iter().position(|z| *z == present).unwrap_or_default();
+    Ok(Select::new("Time zone:", zones)
+        .with_starting_cursor(cursor)
+        .prompt_skippable()?
+        .map(|zone| zone.to_string()))
+}
+
+fn modify_zone(field: &mut String) -> Result<()> {
+    if let Some(zone) = prompt_zone(field)? {
+        *field = zone;
+    }
+    Ok(())
+}
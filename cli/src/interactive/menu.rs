@@ -1,9 +1,8 @@
-use crate::{interactive::custom_input::input_value, APP_SHORTNAME};
+use crate::interactive::custom_input::{input_value, param::input_param};
 use anyhow::Result;
 use contact_manager_lib::{
-    add_or_replace_property,
     api_tools::remove_parameters,
-    delete_properties,
+    changes::Change,
     vcard::{uuids_from_vcards, PROPERTY_NO_MODIFICATION_BY_USER},
     vcard_parser::{
         traits::{HasName, HasParameters, HasValue},
@@ -17,7 +16,7 @@ use promptable::{
     basics::menu::{menu_cancel, menu_confirm, MenuClassic},
 };
 
-use super::{contact::Contact, properties_to_add, validator_param};
+use super::{contact::Contact, pending, properties_to_add, stage};
 
 #[derive(Deref, DerefMut)]
 pub struct PropertyWrapper(pub Property);
@@ -27,8 +26,14 @@ pub struct ParameterWrapper(pub Parameter);
 pub fn menu_properties(contact: &mut Contact) -> Result<()> {
     let options = MenuClassic::consts().to_vec();
     let restore_contact = contact.clone();
+    // changes made in this menu are discarded if the user cancel.
+    let restore_pending = pending().len();
     loop {
         println!("{}", contact.display_human());
+        let nb_pending = pending().len();
+        if nb_pending > 0 {
+            println!("\n{nb_pending} unapplied modifications.");
+        }
         if let Some(choix) = Select::new("Action on Property:\n", options.clone())
             .without_filtering()
             .prompt_skippable()?
@@ -39,6 +44,7 @@ pub fn menu_properties(contact: &mut Contact) -> Result<()> {
                 MenuClassic::DELETE => menu_properties_delete(contact)?,
                 MenuClassic::CANCEL => {
                     if menu_cancel(&restore_contact, contact)? {
+                        pending().truncate(restore_pending);
                         return Ok(());
                     }
                 }
@@ -50,6 +56,7 @@ pub fn menu_properties(contact: &mut Contact) -> Result<()> {
             }
         } else {
             if menu_cancel(&restore_contact, contact)? {
+                pending().truncate(restore_pending);
                 return Ok(());
             }
         }
@@ -77,86 +84,95 @@ fn menu_properties_add(contact: &mut Contact) -> Result<()> {
     {
         if let Some(value) = input_value(&c, None)? {
             let raw = format!("{c}:{value}\n");
-            let property = &Property::create_from_str(&raw)?;
+            let property = Property::create_from_str(&raw)?;
             contact.set_property(&property)?;
-            add_or_replace_property(
-                APP_SHORTNAME,
-                &vec![property],
-                &uuids_from_vcards(&vec![&contact])?,
-            )?;
+            stage(Change::AddProperty {
+                uuid: uuids_from_vcards(&vec![&contact])?[0],
+                property,
+            });
         }
     }
     Ok(())
 }
 fn menu_properties_modify(contact: &mut Contact) -> Result<()> {
-    let contact_restore = contact.clone();
-    let properties = contact.get_properties();
-    if let Some(mut property) = Select::new("Property to modify", properties).prompt_skippable()? {
-        let options = [
-            "Parameters",
-            "Value",
-            MenuClassic::CANCEL,
-            MenuClassic::CONFIRM,
-        ]
-        .to_vec();
-        if let Some(c) = Select::new("Choice", options).prompt_skippable()? {
-            match c {
-                "Parameters" => menu_params(&mut property)?,
-                "Value" => {
-                    let name = property.name().to_string();
-                    input_value(&name, Some(&mut property))?;
-                }
-                MenuClassic::CANCEL => {
-                    if menu_cancel(&contact_restore, contact)? {
-                        return Ok(());
+    let mut properties = contact.get_properties();
+    properties.retain(|p| !PROPERTY_NO_MODIFICATION_BY_USER.contains(&p.name()));
+    let Some(old) = Select::new("Property to modify", properties).prompt_skippable()? else {
+        return Ok(());
+    };
+    let mut property = old.clone();
+    let options = [
+        "Parameters",
+        "Value",
+        MenuClassic::CANCEL,
+        MenuClassic::CONFIRM,
+    ]
+    .to_vec();
+    loop {
+        match Select::new("Choice", options.clone()).prompt_skippable()? {
+            Some("Parameters") => menu_params(&mut property, &altids(contact, &old))?,
+            Some("Value") => {
+                let name = property.name().to_string();
+                input_value(&name, Some(&mut property))?;
+            }
+            Some(MenuClassic::CONFIRM) => {
+                if menu_confirm(
+                    &PropertyWrapper(old.clone()),
+                    &PropertyWrapper(property.clone()),
+                )? {
+                    if property != old {
+                        contact.remove_property(&old)?;
+                        contact.set_property(&property)?;
+                        stage(Change::ModifyProperty {
+                            uuid: uuids_from_vcards(&vec![&contact])?[0],
+                            old,
+                            new: property,
+                        });
                     }
+                    return Ok(());
                 }
-                _ => {
-                    if menu_confirm(&contact_restore, contact)? {
-                        add_or_replace_property(
-                            APP_SHORTNAME,
-                            &vec![&property],
-                            &uuids_from_vcards(&vec![&contact])?,
-                        )?;
-
-                        return Ok(());
-                    }
+            }
+            _ => {
+                if menu_cancel(&old, &mut property)? {
+                    return Ok(());
                 }
             }
         }
     }
-    Ok(())
+}
+/// the ALTID values of the properties of the same name, except the property itself.
+fn altids(contact: &Contact, property: &Property) -> Vec<String> {
+    let mut altids: Vec<String> = contact
+        .get_properties()
+        .iter()
+        .filter(|p| p.name() == property.name() && *p != property)
+        .flat_map(|p| p.get_parameters())
+        .filter(|param| param.name() == "ALTID")
+        .map(|param| param.get_value().to_string().trim_matches('"').to_string())
+        .collect();
+    altids.sort();
+    altids.dedup();
+    altids
 }
 fn menu_properties_delete(contact: &mut Contact) -> Result<()> {
     let mut properties = contact.get_properties();
-    properties.retain(|p| PROPERTY_NO_MODIFICATION_BY_USER.contains(&p.name()) && p.name() != "FN");
+    properties.retain(|p| !PROPERTY_NO_MODIFICATION_BY_USER.contains(&p.name()) && p.name() != "FN");
 
     let choix = MultiSelect::new("Properties to remove", properties).prompt_skippable()?;
     if let Some(vp) = choix {
-        for p in vp.iter() {
+        let uuid = uuids_from_vcards(&vec![&contact])?[0];
+        for p in vp.into_iter() {
             contact.remove_property(&p)?;
+            stage(Change::DeleteProperty { uuid, property: p });
         }
-        delete_properties(
-            APP_SHORTNAME,
-            &vp.iter().map(|p| p).collect(),
-            &uuids_from_vcards(&vec![&contact])?,
-        )?
     }
     Ok(())
 }
 
-fn menu_params_add(property: &mut Property) -> Result<()> {
+fn menu_params_add(property: &mut Property, altids: &[String]) -> Result<()> {
     let params = property.allowed_parameters();
     if let Some(name_param) = Select::new("Param to add", params).prompt_skippable()? {
-        let validator = move |input: &str| {
-            let param_raw = [";", &name_param, "=", input].concat();
-            Ok(validator_param(param_raw)?)
-        };
-
-        if let Ok(value) = Text::new("value of parameter:")
-            .with_validator(validator)
-            .prompt()
-        {
+        if let Some(value) = input_param(property.name(), &name_param, None, altids)? {
             let param_raw = [";", &name_param, "=", &value].concat();
             let param = Parameter::try_from(param_raw.as_str())?;
             property.add_parameter(param)?;
@@ -164,22 +180,12 @@ fn menu_params_add(property: &mut Property) -> Result<()> {
     }
     Ok(())
 }
-fn menu_params_modify(property: &mut Property) -> Result<()> {
+fn menu_params_modify(property: &mut Property, altids: &[String]) -> Result<()> {
     if let Some(param) =
         Select::new("Param to modify", property.get_parameters()).prompt_skippable()?
     {
-        let param_name = param.name().to_string();
-        let validator = move |input: &str| {
-            let param_raw = [";", &param_name, "=", input].concat();
-            Ok(validator_param(param_raw)?)
-        };
-
-        if let Ok(value) = Text::new("value of parameter:")
-            .with_placeholder(&param.get_value().to_string())
-            .with_default(&param.get_value().to_string())
-            .with_validator(validator)
-            .prompt()
-        {
+        let present = param.get_value().to_string();
+        if let Some(value) = input_param(property.name(), param.name(), Some(&present), altids)? {
             let param_raw = [";", &param.name(), "=", &value].concat();
             let new_param = Parameter::try_from(param_raw.as_str())?;
             remove_parameters(property, &vec![param]);
@@ -198,7 +204,8 @@ fn menu_params_delete(property: &mut Property) -> Result<()> {
     Ok(())
 }
 
-fn menu_params(property: &mut Property) -> Result<()> {
+/// altids are the ALTID values used by the other properties of the same name, to group them.
+fn menu_params(property: &mut Property, altids: &[String]) -> Result<()> {
     let property_restore = property.clone();
     let options = MenuClassic::consts().to_vec();
     loop {
@@ -207,8 +214,8 @@ fn menu_params(property: &mut Property) -> Result<()> {
             .prompt_skippable()?
         {
             match c {
-                MenuClassic::ADD => menu_params_add(property)?,
-                MenuClassic::MODIFY => menu_params_modify(property)?,
+                MenuClassic::ADD => menu_params_add(property, altids)?,
+                MenuClassic::MODIFY => menu_params_modify(property, altids)?,
                 MenuClassic::DELETE => menu_params_delete(property)?,
                 MenuClassic::CANCEL => {
                     if menu_cancel(&property_restore, property)? {
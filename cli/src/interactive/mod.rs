@@ -1,21 +1,258 @@
+use std::sync::{Mutex, MutexGuard};
+
 use anyhow::Result;
 use contact_manager_lib::{
     api_tools::cardinals,
+    book::book_info,
+    changes::{discard_session, load_session, Change, ChangeSet},
     paths::{books_directory, books_names},
+    smart::smart_books_names,
+    uuid::Uuid,
+    vcard::uuids_from_vcards,
     vcard_parser::{
         traits::{HasParameters, HasValue},
         vcard::{property::Property, Vcard},
     },
+    new_vcard, vcards_from_book,
 };
 use inquire::validator::Validation;
+use inquire::{Confirm, MultiSelect, Select};
 
-use crate::APP_SHORTNAME;
+use crate::{store, APP_SHORTNAME};
+use book::{Book, VecBook};
+use contact::{Contact, VecContact, WrapperVcard};
 
 pub mod book;
+pub mod bulk;
 pub mod contact;
 pub mod custom_input;
 pub mod display;
 pub mod menu;
+pub mod search;
+
+/// modifications made in the interactive mode, applied to the contacts only when the user confirms them.
+static PENDING: Mutex<ChangeSet> = Mutex::new(ChangeSet::new());
+
+pub fn pending() -> MutexGuard<'static, ChangeSet> {
+    PENDING.lock().expect("the pending changes are never locked twice")
+}
+
+/// keep the modification to apply it later.
+pub fn stage(change: Change) {
+    pending().push(change)
+}
+
+const CHANGES_APPLY: &str = "Apply all";
+const CHANGES_PICK: &str = "Pick the changes to apply";
+const CHANGES_DISCARD: &str = "Discard all";
+const CHANGES_BACK: &str = "Back";
+const CHANGES_SAVE: &str = "Save them to resume later";
+const SESSION_RESUME: &str = "Resume";
+const SESSION_RESUME_WITHOUT_CONFLICTS: &str = "Resume without the changes of the modified contacts";
+const SESSION_DISCARD: &str = "Discard the saved changes";
+
+/// show the differences of the pending changes and let the user apply or discard them.
+/// return true if the contacts or the pending changes were modified, so the representation must be reloaded.
+pub fn menu_changes() -> Result<bool> {
+    if pending().is_empty() {
+        println!("No pending changes.");
+        return Ok(false);
+    }
+    println!("{}", pending().diff(APP_SHORTNAME)?);
+    let options = vec![CHANGES_APPLY, CHANGES_PICK, CHANGES_DISCARD, CHANGES_BACK];
+    match Select::new("Pending changes:\n", options)
+        .without_filtering()
+        .prompt_skippable()?
+    {
+        Some(CHANGES_APPLY) => {
+            // the changes not applied stay pending, for example if a book is read-only.
+            if let Err(e) = pending().apply(store()) {
+                println!("{e}");
+            }
+        }
+        Some(CHANGES_PICK) => {
+            let changes = pending().changes().clone();
+            let all: Vec<usize> = (0..changes.len()).collect();
+            let options: Vec<String> = changes
+                .iter()
+                .map(|c| format!("{}: {}", c.uuid(), c.to_string().replace('\n', " ")))
+                .collect();
+            if let Some(chosen) = MultiSelect::new("Changes to apply:", options)
+                .with_default(&all)
+                .raw_prompt_skippable()?
+            {
+                let indexes = chosen.iter().map(|c| c.index).collect();
+                pending().apply_selected(store(), &indexes)?;
+            }
+            let remaining = pending().len();
+            if remaining > 0
+                && Confirm::new(&format!("Discard the {remaining} changes not applied ?"))
+                    .with_default(false)
+                    .prompt()?
+            {
+                pending().clear();
+            }
+        }
+        Some(CHANGES_DISCARD) => pending().clear(),
+        _ => return Ok(false),
+    }
+    Ok(true)
+}
+
+/// ask what to do of the pending changes before quitting.
+pub fn quit_with_changes() -> Result<()> {
+    if pending().is_empty() {
+        return Ok(());
+    }
+    println!("{}", pending().diff(APP_SHORTNAME)?);
+    let options = vec![CHANGES_APPLY, CHANGES_SAVE, CHANGES_DISCARD];
+    match Select::new("There are unapplied changes, before quitting:", options)
+        .without_filtering()
+        .prompt_skippable()?
+    {
+        Some(CHANGES_APPLY) => pending().apply(store())?,
+        Some(CHANGES_SAVE) => pending().save_session(APP_SHORTNAME)?,
+        _ => {}
+    }
+    Ok(())
+}
+
+/// if changes were saved by a previous session, ask to resume or discard them.
+/// the saved session is deleted, the changes resumed are pending again.
+pub fn resume_session() -> Result<()> {
+    let Some(mut session) = load_session(APP_SHORTNAME)? else {
+        return Ok(());
+    };
+    println!(
+        "Changes were saved by a previous session:\n{}",
+        session.changes.diff(APP_SHORTNAME)?
+    );
+    let mut options = vec![SESSION_RESUME, SESSION_DISCARD];
+    if !session.conflicts.is_empty() {
+        println!("The following contacts were modified or deleted since the changes were saved, their changes may not be valid anymore:");
+        for uuid in session.conflicts.iter() {
+            println!("{uuid}");
+        }
+        options.insert(1, SESSION_RESUME_WITHOUT_CONFLICTS);
+    }
+    match Select::new("Saved changes:", options)
+        .without_filtering()
+        .prompt_skippable()?
+    {
+        Some(SESSION_RESUME) => *pending() = session.changes,
+        Some(SESSION_RESUME_WITHOUT_CONFLICTS) => {
+            for uuid in session.conflicts.iter() {
+                session.changes.discard_contact(uuid);
+            }
+            *pending() = session.changes;
+        }
+        // keep the saved session if the user did not choose.
+        None => return Ok(()),
+        _ => {}
+    }
+    discard_session(APP_SHORTNAME)?;
+    Ok(())
+}
+
+/// load the books from the contacts and apply the changes on them, without writing the changes.
+pub fn load_books(changes: &ChangeSet) -> Result<VecBook> {
+    let mut books = VecBook(Vec::new());
+    // smart books are shown after the books, their contacts can not be added or removed.
+    let mut names = books_names(APP_SHORTNAME)?;
+    names.extend(smart_books_names(APP_SHORTNAME)?);
+    for name in names {
+        books.push(Book {
+            contacts: VecContact(
+                vcards_from_book(store(), Some(&name), false)?
+                    .into_iter()
+                    .map(|v| Contact {
+                        vcard: WrapperVcard(v),
+                    })
+                    .collect(),
+            ),
+            info: book_info(APP_SHORTNAME, &name)?,
+            name,
+        });
+    }
+    for change in changes.changes() {
+        apply_in_memory(&mut books, change)?;
+    }
+    Ok(books)
+}
+
+/// apply the change to the representation of the books, without writing it.
+pub fn apply_in_memory(books: &mut [Book], change: &Change) -> Result<()> {
+    match change {
+        Change::CreateContact {
+            uuid,
+            fullname,
+            book,
+        } => {
+            let contact = Contact {
+                vcard: WrapperVcard(new_vcard(APP_SHORTNAME, book, fullname, uuid)?),
+            };
+            for b in books.iter_mut().filter(|b| &b.name == book) {
+                b.contacts.push(contact.clone());
+            }
+        }
+        Change::AddProperty { uuid, property } => {
+            for contact in contacts_with_uuid(books, uuid) {
+                contact.set_property(property)?;
+            }
+        }
+        Change::ModifyProperty { uuid, old, new } => {
+            for contact in contacts_with_uuid(books, uuid) {
+                contact.remove_property(old)?;
+                contact.set_property(new)?;
+            }
+        }
+        Change::DeleteProperty { uuid, property } => {
+            for contact in contacts_with_uuid(books, uuid) {
+                contact.remove_property(property)?;
+            }
+        }
+        Change::AddToBook { uuid, book } => {
+            let contact = books
+                .iter()
+                .flat_map(|b| b.contacts.iter())
+                .find(|c| contact_uuid(c).ok().as_ref() == Some(uuid))
+                .cloned();
+            if let Some(contact) = contact {
+                for b in books.iter_mut().filter(|b| &b.name == book) {
+                    if !b
+                        .contacts
+                        .iter()
+                        .any(|c| contact_uuid(c).ok().as_ref() == Some(uuid))
+                    {
+                        b.contacts.push(contact.clone());
+                    }
+                }
+            }
+        }
+        Change::RemoveFromBook { uuid, book } => {
+            for b in books.iter_mut().filter(|b| &b.name == book) {
+                b.contacts.retain(|c| contact_uuid(c).ok().as_ref() != Some(uuid));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn contacts_with_uuid<'a>(
+    books: &'a mut [Book],
+    uuid: &'a Uuid,
+) -> impl Iterator<Item = &'a mut Contact> {
+    books
+        .iter_mut()
+        .flat_map(|b| b.contacts.iter_mut())
+        .filter(move |c| contact_uuid(c).ok().as_ref() == Some(uuid))
+}
+
+/// uid of the contact.
+pub fn contact_uuid(contact: &Contact) -> Result<Uuid> {
+    Ok(uuids_from_vcards(&vec![&contact.vcard.0])?[0])
+}
+
 
 fn properties_to_add(vcard: &Vcard) -> Vec<Property> {
     let vcard_properties = vcard.get_properties();
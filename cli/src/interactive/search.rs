@@ -0,0 +1,222 @@
+use anyhow::Result;
+use contact_manager_lib::{
+    tags::has_tag,
+    uuid::Uuid,
+    vcard::property_match,
+    vcard_parser::{
+        constants::PropertyName,
+        traits::{HasName, HasValue},
+        vcard::property::Property,
+    },
+};
+use inquire::{validator::Validation, Select, Text};
+use promptable::basics::display::PromptableDisplay;
+
+use super::{
+    book::Book,
+    contact::Contact,
+    contact_uuid,
+    menu::{menu_properties, PropertyWrapper},
+};
+
+const SEARCH_ALL_BOOKS: &str = "All books";
+const SEARCH_ANY: &str = "Any value";
+const SEARCH_FN: &str = "Full name";
+const SEARCH_EMAIL: &str = "Email";
+const SEARCH_TEL: &str = "Phone";
+const SEARCH_ORG: &str = "Organization";
+const SEARCH_TAG: &str = "Tag";
+const SEARCH_FILTER: &str = "Property filter, like 'EMAIL;TYPE=work:@corp.com'";
+
+/// a contact found by the search, with the properties which matched.
+struct Found {
+    contact: Contact,
+    matched: Vec<Property>,
+}
+
+impl std::fmt::Display for Found {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let snippets = self
+            .matched
+            .iter()
+            .filter(|p| p.name() != PropertyName::FN)
+            .map(|p| PropertyWrapper(p.to_owned()).display_short())
+            .collect::<Vec<String>>()
+            .join(", ");
+        if snippets.is_empty() {
+            write!(f, "{}", self.contact)
+        } else {
+            write!(f, "{} - {snippets}", self.contact)
+        }
+    }
+}
+
+/// the search asked to the user.
+enum Search {
+    /// value contained in any property, ignoring the case.
+    Any(String),
+    /// property matched like the filters of the immediate mode, the value only needs to be contained.
+    Filter(Property),
+    /// tag in the categories.
+    Tag(String),
+}
+
+impl Search {
+    /// the properties of the contact matching the search, empty if it does not match.
+    fn matched(&self, contact: &Contact) -> Result<Vec<Property>> {
+        let properties = contact.get_properties();
+        Ok(match self {
+            Search::Any(value) => {
+                let value = value.to_lowercase();
+                properties
+                    .into_iter()
+                    .filter(|p| p.get_value().to_string().to_lowercase().contains(&value))
+                    .collect()
+            }
+            Search::Filter(filter) => {
+                let mut matched = Vec::new();
+                for p in properties {
+                    if property_match(filter, &p, true)? {
+                        matched.push(p);
+                    }
+                }
+                matched
+            }
+            Search::Tag(tag) => {
+                if has_tag(contact, tag) {
+                    properties
+                        .into_iter()
+                        .filter(|p| p.name() == PropertyName::CATEGORIES)
+                        .collect()
+                } else {
+                    Vec::new()
+                }
+            }
+        })
+    }
+}
+
+/// search the contacts of a book by their properties, then modify the one chosen among the results.
+/// The modifications are kept pending, and shown in every book containing the contact.
+pub fn menu_search(books: &mut [Book]) -> Result<()> {
+    let mut names = vec![SEARCH_ALL_BOOKS.to_string()];
+    names.extend(books.iter().map(|b| b.name.to_owned()));
+    let Some(book) = Select::new("Search in:", names).prompt_skippable()? else {
+        return Ok(());
+    };
+    search_in(books, &book)
+}
+
+/// search the contacts of a single book, from its own menu.
+pub fn search_book(book: &mut Book) -> Result<()> {
+    let name = book.name.to_owned();
+    search_in(std::slice::from_mut(book), &name)
+}
+
+/// search the contacts of the book named, or of every book, and modify the one chosen.
+fn search_in(books: &mut [Book], book: &str) -> Result<()> {
+    let Some(search) = input_search()? else {
+        return Ok(());
+    };
+    let mut found: Vec<Found> = Vec::new();
+    let mut seen: Vec<Uuid> = Vec::new();
+    for b in books
+        .iter()
+        .filter(|b| book == SEARCH_ALL_BOOKS || b.name == book)
+    {
+        for contact in b.contacts.iter() {
+            let uuid = contact_uuid(contact)?;
+            // a contact present in several books is shown once.
+            if seen.contains(&uuid) {
+                continue;
+            }
+            let matched = search.matched(contact)?;
+            if !matched.is_empty() {
+                seen.push(uuid);
+                found.push(Found {
+                    contact: contact.to_owned(),
+                    matched,
+                });
+            }
+        }
+    }
+    if found.is_empty() {
+        println!("No contact found.");
+        return Ok(());
+    }
+    let Some(chosen) =
+        Select::new(&format!("{} contacts found:", found.len()), found).prompt_skippable()?
+    else {
+        return Ok(());
+    };
+    let uuid = contact_uuid(&chosen.contact)?;
+    let mut contact = chosen.contact;
+    menu_properties(&mut contact)?;
+    // the copies of the contact in the other books show the modifications.
+    for c in books
+        .iter_mut()
+        .flat_map(|b| b.contacts.iter_mut())
+        .filter(|c| contact_uuid(c).ok() == Some(uuid))
+    {
+        *c = contact.clone();
+    }
+    Ok(())
+}
+
+/// ask what to search and the value searched.
+fn input_search() -> Result<Option<Search>> {
+    let options = vec![
+        SEARCH_ANY,
+        SEARCH_FN,
+        SEARCH_EMAIL,
+        SEARCH_TEL,
+        SEARCH_ORG,
+        SEARCH_TAG,
+        SEARCH_FILTER,
+    ];
+    let Some(kind) = Select::new("Search by:", options)
+        .without_filtering()
+        .prompt_skippable()?
+    else {
+        return Ok(None);
+    };
+    let name = match kind {
+        SEARCH_FN => Some(PropertyName::FN),
+        SEARCH_EMAIL => Some(PropertyName::EMAIL),
+        SEARCH_TEL => Some(PropertyName::TEL),
+        SEARCH_ORG => Some(PropertyName::ORG),
+        _ => None,
+    };
+    let filter = kind == SEARCH_FILTER;
+    let validator = move |input: &str| {
+        let raw = match name {
+            Some(name) => format!("{name}:{input}\n"),
+            None if filter => format!("{input}\n"),
+            None => return Ok(Validation::Valid),
+        };
+        if (filter && !input.contains(':')) || Property::create_from_str(&raw).is_ok() {
+            Ok(Validation::Valid)
+        } else {
+            Ok(Validation::Invalid(
+                "Value is not correct for this Property".into(),
+            ))
+        }
+    };
+    let Some(input) = Text::new("Search:")
+        .with_validator(validator)
+        .prompt_skippable()?
+    else {
+        return Ok(None);
+    };
+    Ok(Some(match kind {
+        SEARCH_ANY => Search::Any(input),
+        SEARCH_TAG => Search::Tag(input.trim().to_string()),
+        // a filter without value matches every contact having the property.
+        SEARCH_FILTER if !input.contains(':') => Search::Filter(Property::default(input.trim())),
+        SEARCH_FILTER => Search::Filter(Property::create_from_str(&format!("{input}\n"))?),
+        _ => Search::Filter(Property::create_from_str(&format!(
+            "{}:{input}\n",
+            name.expect("the other searches are on a property")
+        ))?),
+    }))
+}
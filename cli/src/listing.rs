@@ -0,0 +1,188 @@
+use anyhow::Result;
+use clap::ValueEnum;
+use contact_manager_lib::{
+    book::{book_info, book_summary},
+    find_books_where_contact_is_present,
+    paths::books_names,
+    vcard::uuids_from_vcards,
+    vcard_parser::{
+        traits::{HasName, HasParameters, HasValue},
+        vcard::{property::Property, Vcard},
+    },
+    vcards_from_book,
+};
+use serde_json::{json, Value};
+
+use crate::{store, APP_SHORTNAME};
+
+/// properties describing the vcard itself, not shown to the user.
+const PROPERTIES_HIDDEN: [&str; 3] = ["BEGIN", "END", "VERSION"];
+
+/// how a contact is printed.
+#[derive(ValueEnum, Clone, Copy, Default)]
+pub enum ShowFormat {
+    /// the properties with their values, one by line.
+    #[default]
+    Human,
+    /// the vcard as stored.
+    Raw,
+    /// an array of objects, one for each contact.
+    Json,
+}
+
+/// print the books with their number of contacts, size, last modification and information.
+pub fn list_books() -> Result<()> {
+    for name in books_names(APP_SHORTNAME)? {
+        let info = book_info(APP_SHORTNAME, &name)?;
+        let summary = book_summary(store(), &name)?;
+        let modified = summary
+            .modified
+            .map(|m| m.format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or("never".to_string());
+        println!(
+            "{name}: {} contacts, {}, last modified {modified}",
+            summary.contacts,
+            human_size(summary.size)
+        );
+        let mut details = Vec::new();
+        if let Some(description) = &info.description {
+            details.push(description.to_owned());
+        }
+        if let Some(color) = &info.color {
+            details.push(format!("color: {color}"));
+        }
+        if !info.categories.is_empty() {
+            details.push(format!("categories: {}", info.categories.join(",")));
+        }
+        if let Some(sort_by) = &info.sort_by {
+            details.push(format!("sorted by: {sort_by}"));
+        }
+        if !details.is_empty() {
+            println!("  {}", details.join(", "));
+        }
+    }
+    Ok(())
+}
+
+fn human_size(size: u64) -> String {
+    match size {
+        0..1024 => format!("{size} B"),
+        1024..1048576 => format!("{:.1} KiB", size as f64 / 1024.0),
+        _ => format!("{:.1} MiB", size as f64 / 1048576.0),
+    }
+}
+
+/// print the whole contacts with the books they belong to.
+/// The raw format only prints the vcards, so the output can be imported.
+pub fn show(vcards: &[Vcard], format: ShowFormat) -> Result<()> {
+    match format {
+        ShowFormat::Raw => {
+            for vcard in vcards {
+                print!("{vcard}");
+            }
+        }
+        ShowFormat::Human => {
+            for (nb, vcard) in vcards.iter().enumerate() {
+                if nb > 0 {
+                    println!();
+                }
+                let fullname = vcard
+                    .get_property_by_name("FN")
+                    .map(|p| p.get_value().to_string())
+                    .unwrap_or_default();
+                println!("{fullname}");
+                println!("books: {}", books_of(vcard)?.join(", "));
+                for property in vcard.get_properties() {
+                    if PROPERTIES_HIDDEN.contains(&property.name()) {
+                        continue;
+                    }
+                    let parameters = parameters(&property)
+                        .iter()
+                        .map(|(name, value)| format!("{name}={value}"))
+                        .collect::<Vec<String>>();
+                    if parameters.is_empty() {
+                        println!("  {}: {}", property.name(), property.get_value());
+                    } else {
+                        println!(
+                            "  {} ({}): {}",
+                            property.name(),
+                            parameters.join(", "),
+                            property.get_value()
+                        );
+                    }
+                }
+            }
+        }
+        ShowFormat::Json => {
+            let mut contacts = Vec::new();
+            for vcard in vcards {
+                let properties: Vec<Value> = vcard
+                    .get_properties()
+                    .iter()
+                    .filter(|p| !PROPERTIES_HIDDEN.contains(&p.name()))
+                    .map(|p| {
+                        json!({
+                            "name": p.name(),
+                            "parameters": parameters(p)
+                                .into_iter()
+                                .map(|(name, value)| (name, Value::from(value)))
+                                .collect::<serde_json::Map<String, Value>>(),
+                            "value": p.get_value().to_string(),
+                        })
+                    })
+                    .collect();
+                contacts.push(json!({
+                    "books": books_of(vcard)?,
+                    "properties": properties,
+                }));
+            }
+            println!("{}", serde_json::to_string_pretty(&contacts)?);
+        }
+    }
+    Ok(())
+}
+
+fn books_of(vcard: &Vcard) -> Result<Vec<String>> {
+    let uuid = uuids_from_vcards(&vec![vcard])?[0];
+    Ok(find_books_where_contact_is_present(&uuid, APP_SHORTNAME)?)
+}
+
+/// names and values of the parameters of a property.
+fn parameters(property: &Property) -> Vec<(String, String)> {
+    property
+        .get_parameters()
+        .iter()
+        .map(|p| (p.name().to_string(), p.get_value().to_string()))
+        .collect()
+}
+
+/// print the number of contacts having each property, the most used first.
+pub fn stats(book_name: Option<&str>, recursive: bool) -> Result<()> {
+    let vcards = vcards_from_book(store(), book_name, recursive)?;
+    let mut coverage: Vec<(String, usize)> = Vec::new();
+    for vcard in vcards.iter() {
+        let mut names: Vec<String> = vcard
+            .get_properties()
+            .iter()
+            .map(|p| p.name().to_string())
+            .filter(|name| !PROPERTIES_HIDDEN.contains(&name.as_str()))
+            .collect();
+        names.sort();
+        names.dedup();
+        for name in names {
+            match coverage.iter_mut().find(|(n, _)| n == &name) {
+                Some((_, nb)) => *nb += 1,
+                None => coverage.push((name, 1)),
+            }
+        }
+    }
+    coverage.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    println!("{} contacts", vcards.len());
+    if book_name.is_none() {
+        println!("{} books", books_names(APP_SHORTNAME)?.len());
+    }
+    for (name, nb) in coverage {
+        println!("{name}: {nb} ({}%)", nb * 100 / vcards.len());
+    }
+    Ok(())
+}
@@ -1,13 +1,32 @@
-use anyhow::{bail, Context, Result};
+use std::{collections::HashMap, path::Path, sync::OnceLock};
+
+use anyhow::{anyhow, bail, Context, Result};
 use args::Book;
-use args::{Cli, ImmediateMode};
-use clap::Parser;
+use args::{Cli, ConfigAction, ImmediateMode, Logic, PropertyArg1, SmartBookAction, TagAction};
+use clap::{CommandFactory, Parser};
+use clap_complete::CompleteEnv;
 use contact_manager_lib::{
-    add_or_replace_property, add_to_book, create_book, create_contact, delete_book,
-    delete_contacts, export, find_properties, find_uids, generate_index, import,
+    access::{set_book_read_only, Store},
+    address::Address,
+    add_or_replace_property, add_to_book,
+    book::{book_info, set_book_info},
+    changes::journal,
+    copy_contacts, create_book, create_contact, delete_book, delete_contacts, export,
+    find_properties, find_uids, generate_index, import, import_from_source, modified_since,
+    move_contacts,
     paths::books_directory,
-    remove_from_book, rename_book,
-    vcard_parser::{traits::HasValue, vcard::property::Property},
+    remove_from_book, rename_book, replace_in_values, replace_pattern,
+    smart::{delete_smart_book, save_smart_book, smart_book, smart_books_names, SmartBook},
+    tags::{find_uids_by_tags, tag_changes, tag_cloud, TagOperation},
+    template::bulk_set_changes,
+    uuid::Uuid,
+    vcard::uuids_from_vcards,
+    vcard_parser::{
+        constants::PropertyName,
+        traits::{HasName, HasValue},
+        vcard::{property::Property, Vcard},
+    },
+    vcards_from_book,
 };
 #[cfg(feature = "interact")]
 use interactive::book::ShortCutArgBook;
@@ -15,20 +34,43 @@ use promptable::basics::display::clear_screen;
 use promptable::basics::promptable::Promptable;
 use promptable::inspect::Inspectable;
 mod args;
+mod completion;
+mod config;
+mod email;
+mod listing;
+mod phone;
 #[cfg(feature = "interact")]
 mod interactive;
+#[cfg(feature = "tui")]
+mod tui;
 pub const APP_SHORTNAME: &str = "cm";
+/// contacts of cm, with the access mode given by the arguments.
+static STORE: OnceLock<Store> = OnceLock::new();
+
+/// the contacts given to the functions of the library.
+pub fn store() -> &'static Store {
+    STORE.get_or_init(|| Store::new(APP_SHORTNAME))
+}
 fn main() -> Result<()> {
+    // the configuration can change the data directory, it is read before any access to the contacts.
+    config::load()?;
+    // answer the shell if it is asking for completions, before any output.
+    CompleteEnv::with_factory(Cli::command).complete();
     // directory with all contacts files is contacts
     // directory for books is books
     // default book directory is default.
     // create contacts and books and default book directory if does not exist.
     let dir_books = books_directory(APP_SHORTNAME).context("ici")?;
     let mut default_book = dir_books;
-    default_book.push("default");
+    default_book.push(config::config().default_book());
     std::fs::create_dir_all(default_book)?;
-    // parse command line arguments.
-    let args = Cli::parse();
+    // parse command line arguments, the aliases of the configuration being replaced by their command.
+    let args = Cli::parse_from(config::expand_aliases(std::env::args().collect()));
+    // the access mode is set once, before any access to the contacts.
+    // A store used before would have the default mode, and the mode asked would be ignored.
+    STORE
+        .set(Store::new(APP_SHORTNAME).with_mode(args.access_mode()))
+        .map_err(|_| anyhow!("store initialised before the access mode was set"))?;
 
     // execute actions from arguments.
     actions(args)?;
@@ -50,19 +92,19 @@ fn actions(cli: Cli) -> Result<()> {
 #[cfg(feature = "interact")]
 fn shortcut_book(s: ShortCutArgBook, book_name: String) -> Result<()> {
     use clap_shortcuts::ShortCuts;
-    use contact_manager_lib::vcards_from_book;
     use interactive::book::Book as PromptBook;
     use interactive::contact::{Contact, VecContact, WrapperVcard};
     use promptable::termion::screen::{ToAlternateScreen, ToMainScreen};
     let mut book = PromptBook {
         contacts: VecContact(
-            vcards_from_book(APP_SHORTNAME, Some(&book_name))?
+            vcards_from_book(store(), Some(&book_name), false)?
                 .into_iter()
                 .map(|v| Contact {
                     vcard: WrapperVcard(v),
                 })
                 .collect(),
         ),
+        info: book_info(APP_SHORTNAME, &book_name)?,
         name: book_name,
     };
     print!("{}", ToAlternateScreen);
@@ -73,6 +115,8 @@ fn shortcut_book(s: ShortCutArgBook, book_name: String) -> Result<()> {
         book.shortcut_ref(&s, ())?;
     }
     print!("{}", ToMainScreen);
+    // the modifications made by a search are pending until reviewed.
+    interactive::quit_with_changes()?;
     Ok(())
 }
 
@@ -82,43 +126,42 @@ fn interact_mode() -> Result<()> {
     print!("{}", ToAlternateScreen);
     clear_screen();
     use crate::interactive::book::VecBook;
-    use contact_manager_lib::paths::books_names;
-    use contact_manager_lib::vcards_from_book;
     use inquire::Select;
-    use interactive::book::Book as PromptBook;
-    use interactive::contact::{Contact, VecContact, WrapperVcard};
+    use interactive::{
+        bulk::menu_bulk, load_books, menu_changes, pending, quit_with_changes, resume_session,
+        search::menu_search,
+    };
+    resume_session()?;
     // get books structs
-
-    let options = vec!["Manage", "Inspect", "Quit"];
-    let mut books = VecBook(Vec::new());
-    for book in books_names(APP_SHORTNAME)? {
-        books.push(PromptBook {
-            contacts: VecContact(
-                vcards_from_book(APP_SHORTNAME, Some(&book))?
-                    .into_iter()
-                    .map(|v| Contact {
-                        vcard: WrapperVcard(v),
-                    })
-                    .collect(),
-            ),
-            name: book,
-        });
-    }
+    let mut books = load_books(&pending())?;
     loop {
         // clear_screen();
-        if let Some(choice) = Select::new("Contact-Manager\n", options.clone())
+        let changes = format!("Changes ({} pending)", pending().len());
+        let options = vec!["Manage", "Search", "Bulk actions", "Inspect", changes.as_str(), "Quit"];
+        if let Some(choice) = Select::new("Contact-Manager\n", options)
             .without_filtering()
             .prompt_skippable()?
         {
             match choice {
                 "Manage" => {
                     books.modify_by_prompt(())?;
+                    // the contacts added, moved or deleted are pending, every book must show them.
+                    books = load_books(&pending())?;
                 }
+                "Search" => menu_search(&mut books)?,
+                "Bulk actions" => menu_bulk(&mut books)?,
                 "Inspect" => VecBook::inspect_menu(&books)?,
-                _ => break,
+                "Quit" => break,
+                _ => {
+                    // the representation must show the data after applying or discarding.
+                    if menu_changes()? {
+                        books = load_books(&pending())?;
+                    }
+                }
             }
         }
     }
+    quit_with_changes()?;
     print!("{}", ToMainScreen);
     Ok(())
     // option to modify book and contacts. Adding a client suggest to add from another book or to create one.
@@ -127,17 +170,158 @@ fn interact_mode() -> Result<()> {
     // manage contacts and books
 }
 
+fn tag(action: TagAction) -> Result<()> {
+    match action {
+        TagAction::Add {
+            tags,
+            book,
+            find_filters,
+            lo,
+        } => {
+            let uuids = find_contacts(book_name(&book), false, &find_filters, &lo)?;
+            Ok(tag_changes(APP_SHORTNAME, &uuids, &TagOperation::Add(tags))?.apply(store())?)
+        }
+        TagAction::Remove {
+            tags,
+            book,
+            find_filters,
+            lo,
+        } => {
+            let uuids = find_contacts(book_name(&book), false, &find_filters, &lo)?;
+            Ok(tag_changes(APP_SHORTNAME, &uuids, &TagOperation::Remove(tags))?
+                .apply(store())?)
+        }
+        TagAction::Rename { from, to, book } => {
+            let uuids = find_uids_by_tags(store(), book_name(&book), false, &[from.to_owned()])?;
+            Ok(tag_changes(APP_SHORTNAME, &uuids, &TagOperation::Rename { from, to })?
+                .apply(store())?)
+        }
+        TagAction::List { book } => {
+            for (tag, nb) in tag_cloud(store(), book_name(&book))? {
+                println!("{tag}: {nb}");
+            }
+            Ok(())
+        }
+    }
+}
+
+fn smart(action: SmartBookAction) -> Result<()> {
+    match action {
+        SmartBookAction::Save {
+            name,
+            book,
+            find_filters,
+            lo,
+        } => {
+            let query = SmartBook {
+                book: book.map(|b| b.name),
+                filters: find_filters
+                    .filter
+                    .iter()
+                    .map(|p| p.export().trim_end().to_string())
+                    .collect(),
+                operator: lo.operator(),
+                forgive: find_filters.forgive(),
+                tags: find_filters.tag,
+            };
+            Ok(save_smart_book(store(), &name, &query)?)
+        }
+        SmartBookAction::Delete { name } => Ok(delete_smart_book(store(), &name)?),
+        SmartBookAction::List => {
+            for name in smart_books_names(APP_SHORTNAME)? {
+                let query = smart_book(APP_SHORTNAME, &name)?;
+                let mut details = vec![format!(
+                    "in {}",
+                    query.book.as_deref().unwrap_or("all books")
+                )];
+                if !query.filters.is_empty() {
+                    details.push(format!(
+                        "{:?} of {}",
+                        query.operator,
+                        query.filters.join(", ")
+                    ));
+                }
+                if query.forgive {
+                    details.push("forgiving".to_string());
+                }
+                if !query.tags.is_empty() {
+                    details.push(format!("tags: {}", query.tags.join(",")));
+                }
+                println!("{name}: {}", details.join(", "));
+            }
+            Ok(())
+        }
+    }
+}
+
+/// uids of the contacts matched by the filters and having the tags of the arguments, from book or all.
+fn find_contacts(
+    book_name: Option<&str>,
+    recursive: bool,
+    find_filters: &PropertyArg1,
+    lo: &Logic,
+) -> Result<Vec<Uuid>> {
+    // only the tags are used when no filter is given.
+    if find_filters.filter.is_empty() {
+        return Ok(find_uids_by_tags(
+            store(),
+            book_name,
+            recursive,
+            &find_filters.tag,
+        )?);
+    }
+    let mut uuids = find_uids(
+        store(),
+        book_name,
+        recursive,
+        &find_filters.filter,
+        &lo.operator(),
+        find_filters.forgive(),
+    )?;
+    if !find_filters.tag.is_empty() {
+        let tagged = find_uids_by_tags(store(), book_name, recursive, &find_filters.tag)?;
+        uuids.retain(|uuid| tagged.contains(uuid));
+    }
+    Ok(uuids)
+}
+
 fn immediate_mode(args: ImmediateMode) -> Result<()> {
     match args {
-        ImmediateMode::NewBook { new_book } => Ok(create_book(&new_book, APP_SHORTNAME)?),
+        ImmediateMode::NewBook { new_book } => Ok(create_book(&new_book, store())?),
         ImmediateMode::RenameBook { book, new_name } => {
-            Ok(rename_book(&book.name, &new_name, APP_SHORTNAME)?)
+            Ok(rename_book(&book.name, &new_name, store())?)
+        }
+        ImmediateMode::DeleteBook { book } => Ok(delete_book(&book.name, store())?),
+        ImmediateMode::ListBooks => listing::list_books(),
+        ImmediateMode::SetBookInfo {
+            book,
+            description,
+            color,
+            categories,
+            sort_by,
+        } => {
+            let mut info = book_info(APP_SHORTNAME, &book.name)?;
+            if description.is_some() {
+                info.description = description;
+            }
+            if color.is_some() {
+                info.color = color;
+            }
+            if let Some(categories) = categories {
+                info.categories = categories;
+            }
+            if sort_by.is_some() {
+                info.sort_by = sort_by;
+            }
+            Ok(set_book_info(store(), &book.name, &info)?)
+        }
+        ImmediateMode::ReadOnlyBook { book, writable } => {
+            Ok(set_book_read_only(store(), &book.name, !writable)?)
         }
-        ImmediateMode::DeleteBook { book } => Ok(delete_book(&book.name, APP_SHORTNAME)?),
         ImmediateMode::CreateContact { book, value_fn } => {
             let book_default = Book::default();
             create_contact(
-                APP_SHORTNAME,
+                store(),
                 &book.as_ref().unwrap_or(&book_default).name,
                 &value_fn,
             )?;
@@ -150,45 +334,53 @@ fn immediate_mode(args: ImmediateMode) -> Result<()> {
         } => {
             let book_name = book.as_ref().map(|b| b.name.as_str());
             Ok(delete_contacts(
-                &find_uids(
-                    APP_SHORTNAME,
-                    book_name,
-                    &find_filters.filter,
-                    &lo.operator,
-                    find_filters.forgive,
-                )?,
-                APP_SHORTNAME,
+                &find_contacts(book_name, false, &find_filters, &lo)?,
+                store(),
             )?)
         }
+        ImmediateMode::Copy {
+            from_to,
+            find_filters,
+            lo,
+        } => Ok(copy_contacts(
+            store(),
+            &from_to.from,
+            &from_to.to,
+            &find_contacts(Some(&from_to.from), false, &find_filters, &lo)?,
+        )?),
+        ImmediateMode::Move {
+            from_to,
+            find_filters,
+            lo,
+        } => Ok(move_contacts(
+            store(),
+            &from_to.from,
+            &from_to.to,
+            &find_contacts(Some(&from_to.from), false, &find_filters, &lo)?,
+        )?),
         ImmediateMode::Addto {
             book,
             find_filters,
             lo,
-        } => Ok(add_to_book(
-            APP_SHORTNAME,
-            &book.name,
-            &find_uids(
-                APP_SHORTNAME,
-                Some(book.name.as_str()),
-                &find_filters.filter,
-                &lo.operator,
-                find_filters.forgive,
-            )?,
-        )?),
+        } => {
+            // the contacts are searched in all books, without the ones already in the book.
+            let present = uuids_from_vcards(
+                &vcards_from_book(store(), Some(&book.name), false)?
+                    .iter()
+                    .collect(),
+            )?;
+            let mut uuids = find_contacts(None, false, &find_filters, &lo)?;
+            uuids.retain(|uuid| !present.contains(uuid));
+            Ok(add_to_book(store(), &book.name, &uuids)?)
+        }
         ImmediateMode::Removefrom {
             book,
             find_filters,
             lo,
         } => Ok(remove_from_book(
-            APP_SHORTNAME,
+            store(),
             &book.name,
-            &find_uids(
-                APP_SHORTNAME,
-                Some(book.name.as_str()),
-                &find_filters.filter,
-                &lo.operator,
-                find_filters.forgive,
-            )?,
+            &find_contacts(Some(&book.name), false, &find_filters, &lo)?,
         )?),
         ImmediateMode::FindValue {
             book,
@@ -196,19 +388,20 @@ fn immediate_mode(args: ImmediateMode) -> Result<()> {
             lo,
             show_filter,
             pretty,
+            modified_since: since,
+            recursive,
         } => {
+            let mut uuids = find_contacts(book_name(&book), recursive, &find_filters, &lo)
+                .context("Invalid vcard content in contacts stored.")?;
+            if let Some(since) = since {
+                let modified = modified_since(store(), book_name(&book), recursive, &since)?;
+                uuids.retain(|uuid| modified.contains(uuid));
+            }
             let uid_properties = find_properties(
-                APP_SHORTNAME,
+                store(),
                 &show_filter.show,
-                &find_uids(
-                    APP_SHORTNAME,
-                    book_name(&book),
-                    &find_filters.filter,
-                    &lo.operator,
-                    find_filters.forgive,
-                )
-                .context("Invalid vcard content in contacts stored.")?,
-                find_filters.forgive,
+                &uuids,
+                find_filters.forgive(),
             )?;
             // rendu
             if uid_properties.is_empty() {
@@ -228,23 +421,33 @@ fn immediate_mode(args: ImmediateMode) -> Result<()> {
                 }
             } else if len == 0 {
                 for p in uid_properties[0].1.iter() {
-                    println!("{}", p.get_value());
+                    println!("{}", pretty_value(p));
                 }
             } else if len > 0 {
-                // afficher le full name si plusieurs contacts
+                // name the contacts with the template of the configuration if several are found.
+                let display = config::config().display_contact()?;
+                let vcards: HashMap<Uuid, Vcard> =
+                    vcards_from_book(store(), book_name(&book), recursive)?
+                        .into_iter()
+                        .filter_map(|v| Some((uuids_from_vcards(&vec![&v]).ok()?[0], v)))
+                        .collect();
                 for (nb, (u, ps)) in uid_properties.into_iter().enumerate() {
-                    // aller chercher full name
-                    let fullname = find_properties(
-                        APP_SHORTNAME,
-                        &vec![Property::default("FN")],
-                        &vec![u],
-                        find_filters.forgive,
-                    )?;
-                    let fullname = fullname[0].1[0].get_value();
-                    println!("{}:", fullname);
+                    match vcards.get(&u).map(|v| display.render_value(v)).transpose()?.flatten() {
+                        Some(name) => println!("{name}"),
+                        // a property of the template is missing, the full name is always present.
+                        None => {
+                            let fullname = find_properties(
+                                store(),
+                                &vec![Property::default("FN")],
+                                &vec![u],
+                                false,
+                            )?;
+                            println!("{}:", fullname[0].1[0].get_value());
+                        }
+                    }
                     // TODO if some properties have the same name, show also the parameters for those.
                     for p in ps {
-                        println!("{}", p.get_value());
+                        println!("{}", pretty_value(&p));
                     }
                     if nb < len {
                         println!();
@@ -261,18 +464,79 @@ fn immediate_mode(args: ImmediateMode) -> Result<()> {
             properties,
         } => {
             add_or_replace_property(
-                APP_SHORTNAME,
+                store(),
                 &properties.show.iter().map(|p| p).collect(),
-                &find_uids(
-                    APP_SHORTNAME,
-                    book_name(&book),
-                    &find_filters.filter,
-                    &lo.operator,
-                    find_filters.forgive,
-                )?,
+                &find_contacts(book_name(&book), false, &find_filters, &lo)?,
             )?;
             Ok(())
         }
+        ImmediateMode::BulkSet {
+            template,
+            book,
+            find_filters,
+            lo,
+            dry_run,
+            yes,
+        } => {
+            let uuids = find_contacts(book_name(&book), false, &find_filters, &lo)?;
+            let (mut changes, skipped) = bulk_set_changes(store(), &uuids, &template)?;
+            if !skipped.is_empty() {
+                println!(
+                    "{} contacts skipped, a property of the template is absent from them.",
+                    skipped.len()
+                );
+            }
+            if changes.is_empty() {
+                println!("no contact to modify.");
+                return Ok(());
+            }
+            print!("{}", changes.diff(APP_SHORTNAME)?);
+            if dry_run || !(yes || confirm("Apply these modifications ?")?) {
+                return Ok(());
+            }
+            Ok(changes.apply(store())?)
+        }
+        ImmediateMode::Replace {
+            property,
+            from,
+            to,
+            regex,
+            book,
+            find_filters,
+            lo,
+            dry_run,
+            yes,
+        } => {
+            let uuids = match find_filters {
+                Some(find_filters) => find_contacts(book_name(&book), false, &find_filters, &lo)?,
+                None => uuids_from_vcards(
+                    &vcards_from_book(store(), book_name(&book), false)?
+                        .iter()
+                        .collect(),
+                )?,
+            };
+            let (pattern, to) = replace_pattern(&from, &to, regex)?;
+            let mut changes = replace_in_values(store(), &uuids, &property, &pattern, &to)?;
+            if changes.is_empty() {
+                println!("no value to replace.");
+                return Ok(());
+            }
+            print!("{}", changes.diff(APP_SHORTNAME)?);
+            if dry_run || !(yes || confirm("Apply these modifications ?")?) {
+                return Ok(());
+            }
+            Ok(changes.apply(store())?)
+        }
+        ImmediateMode::Journal { since } => {
+            for (time, change) in journal(APP_SHORTNAME)? {
+                if since.is_some_and(|since| time < since) {
+                    continue;
+                }
+                println!("{} {}:", time.format("%Y-%m-%d %H:%M:%S"), change.uuid());
+                println!("{change}");
+            }
+            Ok(())
+        }
         ImmediateMode::RemoveProperty {
             book,
             find_filters,
@@ -280,39 +544,156 @@ fn immediate_mode(args: ImmediateMode) -> Result<()> {
             properties,
         } => {
             add_or_replace_property(
-                APP_SHORTNAME,
+                store(),
                 &properties.show.iter().map(|p| p).collect(),
-                &find_uids(
-                    APP_SHORTNAME,
-                    book_name(&book),
-                    &find_filters.filter,
-                    &lo.operator,
-                    find_filters.forgive,
-                )?,
+                &find_contacts(book_name(&book), false, &find_filters, &lo)?,
             )?;
             Ok(())
         }
         ImmediateMode::GenerateIndex { book, properties } => {
-            let index = generate_index(APP_SHORTNAME, book_name(&book), &properties.filter)?;
+            let properties = if properties.is_empty() {
+                config::config()
+                    .index_columns
+                    .iter()
+                    .map(|c| Property::default(c))
+                    .collect()
+            } else {
+                properties
+            };
+            if properties.is_empty() {
+                bail!("no property given and no index columns in the configuration.");
+            }
+            let index = generate_index(store(), book_name(&book), &properties)?;
             println!("{}", index.join("\n"));
             Ok(())
         }
         ImmediateMode::Import {
             path_vcards_file,
             book,
-        } => Ok(import(
-            &path_vcards_file,
-            &book.unwrap_or_default(),
-            APP_SHORTNAME,
-        )?),
-        ImmediateMode::Export { book } => {
-            Ok(println!("{}", export(book_name(&book), APP_SHORTNAME)?))
+            source,
+        } => match source {
+            Some(source) => Ok(import_from_source(
+                &path_vcards_file,
+                &book.unwrap_or_default(),
+                store(),
+                &source,
+            )?),
+            None => Ok(import(
+                &path_vcards_file,
+                &book.unwrap_or_default(),
+                store(),
+            )?),
+        },
+        ImmediateMode::Show {
+            book,
+            find_filters,
+            lo,
+            format,
+            recursive,
+        } => {
+            let uuids = find_contacts(book_name(&book), recursive, &find_filters, &lo)?;
+            let vcards: Vec<_> = vcards_from_book(store(), book_name(&book), recursive)?
+                .into_iter()
+                .filter(|v| {
+                    uuids_from_vcards(&vec![v]).is_ok_and(|uuid| uuids.contains(&uuid[0]))
+                })
+                .collect();
+            listing::show(&vcards, format)
         }
+        ImmediateMode::Tag { action } => tag(action),
+        ImmediateMode::Config { action } => config_action(action),
+        ImmediateMode::SmartBook { action } => smart(action),
+        ImmediateMode::Stats { book, recursive } => listing::stats(book_name(&book), recursive),
+        ImmediateMode::Export { book, recursive } => Ok(println!(
+            "{}",
+            export(book_name(&book), recursive, store())?
+        )),
+        ImmediateMode::AddFromEmail {
+            path_email,
+            book,
+            yes,
+        } => email::add_from_email(path_email.as_deref(), &book.unwrap_or_default(), yes),
+        ImmediateMode::Completions { shell } => completion::print_completions(shell),
+        ImmediateMode::Manpage { output_dir } => manpages(output_dir.as_deref()),
+        #[cfg(feature = "tui")]
+        ImmediateMode::Tui => tui::tui_mode(),
 
         ImmediateMode::Shortcut { shortcut, book } => Ok(shortcut_book(shortcut, book.name)?),
     }
 }
 
+/// ask to confirm the modifications shown.
+#[cfg(feature = "interact")]
+fn confirm(message: &str) -> Result<bool> {
+    Ok(inquire::Confirm::new(message)
+        .with_default(false)
+        .prompt_skippable()?
+        .unwrap_or_default())
+}
+
+/// ask which of the modifications are applied, all are selected at first.
+#[cfg(feature = "interact")]
+pub fn confirm_each<T: std::fmt::Display>(
+    message: &str,
+    modifications: Vec<T>,
+) -> Result<Vec<T>> {
+    let all: Vec<usize> = (0..modifications.len()).collect();
+    Ok(inquire::MultiSelect::new(message, modifications)
+        .with_default(&all)
+        .prompt_skippable()?
+        .unwrap_or_default())
+}
+
+#[cfg(not(feature = "interact"))]
+fn confirm(_message: &str) -> Result<bool> {
+    bail!(NO_CONFIRMATION)
+}
+
+#[cfg(not(feature = "interact"))]
+pub fn confirm_each<T>(_message: &str, _modifications: Vec<T>) -> Result<Vec<T>> {
+    bail!(NO_CONFIRMATION)
+}
+
+#[cfg(not(feature = "interact"))]
+const NO_CONFIRMATION: &str = "the binary has not been build with the feature \"interact\", use --yes to apply the modifications without confirmation";
+
+/// write the man pages of cm and every command to the directory, or print the man page of cm.
+fn manpages(output_dir: Option<&Path>) -> Result<()> {
+    let cmd = Cli::command()
+        .name(APP_SHORTNAME)
+        .bin_name(APP_SHORTNAME);
+    if let Some(dir) = output_dir {
+        std::fs::create_dir_all(dir)?;
+        clap_mangen::generate_to(cmd, dir)?;
+    } else {
+        clap_mangen::Man::new(cmd).render(&mut std::io::stdout())?;
+    }
+    Ok(())
+}
+
+/// the value of a property for a human, addresses being written on several lines.
+/// Phone numbers of the region of the configuration are written in their national form.
+fn pretty_value(property: &Property) -> String {
+    if let (PropertyName::TEL, Some(region)) = (property.name(), &config::config().phone_region) {
+        return phone::national(region, &property.get_value().to_string());
+    }
+    match Address::from_property(property) {
+        Ok(address) => address.lines().join("\n"),
+        Err(_) => property.get_value().to_string(),
+    }
+}
+
+fn config_action(action: ConfigAction) -> Result<()> {
+    match action {
+        ConfigAction::Get { key } => match config::get(&key)? {
+            Some(value) => Ok(println!("{value}")),
+            None => bail!("{key} is not set in the configuration."),
+        },
+        ConfigAction::Set { key, value } => config::set(&key, &value),
+        ConfigAction::Show => Ok(print!("{}", config::show()?)),
+    }
+}
+
 fn book_name(book: &Option<Book>) -> Option<&str> {
     if let Some(b) = &book {
         Some(b.name.as_str())
@@ -0,0 +1,52 @@
+/// regions with their calling code and the prefix of their numbers in national form.
+const REGIONS: [(&str, &str, &str); 20] = [
+    ("AT", "43", "0"),
+    ("AU", "61", "0"),
+    ("BE", "32", "0"),
+    ("BR", "55", "0"),
+    ("CA", "1", ""),
+    ("CH", "41", "0"),
+    ("DE", "49", "0"),
+    ("DK", "45", ""),
+    ("ES", "34", ""),
+    ("FR", "33", "0"),
+    ("GB", "44", "0"),
+    ("IE", "353", "0"),
+    ("IT", "39", ""),
+    ("JP", "81", "0"),
+    ("LU", "352", ""),
+    ("NL", "31", "0"),
+    ("NO", "47", ""),
+    ("PT", "351", ""),
+    ("SE", "46", "0"),
+    ("US", "1", ""),
+];
+
+/// true if the region is known, by its ISO code.
+pub fn is_known_region(region: &str) -> bool {
+    REGIONS
+        .iter()
+        .any(|(code, _, _)| code.eq_ignore_ascii_case(region))
+}
+
+/// the number in national form if it is in international form with the calling code of the region.
+/// Other numbers are returned as written.
+pub fn national(region: &str, number: &str) -> String {
+    let written = number.strip_prefix("tel:").unwrap_or(number);
+    let Some((_, calling_code, prefix)) = REGIONS
+        .iter()
+        .find(|(code, _, _)| code.eq_ignore_ascii_case(region))
+    else {
+        return written.to_string();
+    };
+    match written
+        .strip_prefix('+')
+        .and_then(|n| n.strip_prefix(calling_code))
+    {
+        Some(rest) => format!(
+            "{prefix}{}",
+            rest.trim_start_matches([' ', '-', '.', '(', ')'])
+        ),
+        None => written.to_string(),
+    }
+}
@@ -0,0 +1,416 @@
+use std::fmt::Display;
+
+use anyhow::{bail, Result};
+use contact_manager_lib::{
+    changes::{Change, ChangeSet},
+    uuid::Uuid,
+    vcard::PROPERTY_NO_MODIFICATION_BY_USER,
+    vcard_parser::{
+        constants::PropertyName,
+        traits::{HasCardinality, HasName, HasValue},
+        vcard::property::Property,
+    },
+};
+use ratatui::{crossterm::event::KeyCode, widgets::ListState};
+
+use crate::interactive::{
+    apply_in_memory, book::Book, contact::Contact, contact_uuid, load_books,
+};
+use crate::store;
+
+/// pane receiving the keys.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Focus {
+    Books,
+    Contacts,
+    Properties,
+}
+
+/// what the keys are used for.
+pub enum Mode {
+    Normal,
+    /// typing the search of the contact list.
+    Search,
+    /// typing a value, applied on Enter.
+    Input(Input, String),
+    /// choosing the book where the selected contact will be moved.
+    MoveTo(ListState),
+}
+
+/// value being typed in Mode::Input.
+#[derive(Clone, Copy)]
+pub enum Input {
+    NewContact,
+    NewProperty,
+    EditProperty,
+}
+
+impl Display for Input {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Input::NewContact => write!(f, "Full name of the new contact: "),
+            Input::NewProperty => write!(f, "New property (NAME;PARAM=VALUE:value): "),
+            Input::EditProperty => write!(f, "Property: "),
+        }
+    }
+}
+
+pub struct App {
+    pub books: Vec<Book>,
+    pub books_state: ListState,
+    pub contacts_state: ListState,
+    pub properties_state: ListState,
+    pub focus: Focus,
+    pub mode: Mode,
+    pub search: String,
+    /// modifications shown in the interface but not yet written to the contacts.
+    pub pending: ChangeSet,
+    pub message: Option<String>,
+    pub quit: bool,
+}
+
+impl App {
+    pub fn new() -> Result<App> {
+        Ok(App {
+            books: load_books(&ChangeSet::new())?.0,
+            books_state: ListState::default().with_selected(Some(0)),
+            contacts_state: ListState::default().with_selected(Some(0)),
+            properties_state: ListState::default().with_selected(Some(0)),
+            focus: Focus::Books,
+            mode: Mode::Normal,
+            search: String::new(),
+            pending: ChangeSet::new(),
+            message: None,
+            quit: false,
+        })
+    }
+
+    pub fn selected_book(&self) -> Option<&Book> {
+        self.books.get(self.books_state.selected()?)
+    }
+
+    /// contacts of the selected book matching the search, on the full name or any value.
+    pub fn visible_contacts(&self) -> Vec<&Contact> {
+        let search = self.search.to_lowercase();
+        self.selected_book()
+            .map(|b| {
+                b.contacts
+                    .iter()
+                    .filter(|c| {
+                        c.get_properties()
+                            .iter()
+                            .any(|p| p.get_value().to_string().to_lowercase().contains(&search))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn selected_contact(&self) -> Option<&Contact> {
+        self.visible_contacts()
+            .get(self.contacts_state.selected()?)
+            .copied()
+    }
+
+    pub fn selected_property(&self) -> Option<Property> {
+        self.selected_contact()?
+            .get_properties()
+            .get(self.properties_state.selected()?)
+            .cloned()
+    }
+
+    /// names of the books where the contact is present, in the representation.
+    pub fn books_of(&self, uuid: &Uuid) -> Vec<String> {
+        self.books
+            .iter()
+            .filter(|b| {
+                b.contacts
+                    .iter()
+                    .any(|c| contact_uuid(c).ok().as_ref() == Some(uuid))
+            })
+            .map(|b| b.name.clone())
+            .collect()
+    }
+
+    /// names of the books other than the selected one.
+    pub fn other_books(&self) -> Vec<String> {
+        let selected = self.selected_book().map(|b| b.name.clone());
+        self.books
+            .iter()
+            .map(|b| b.name.clone())
+            .filter(|n| Some(n) != selected.as_ref())
+            .collect()
+    }
+
+    pub fn on_key(&mut self, key: KeyCode) -> Result<()> {
+        self.message = None;
+        match &mut self.mode {
+            Mode::Normal => self.on_key_normal(key)?,
+            Mode::Search => match key {
+                KeyCode::Char(c) => self.search.push(c),
+                KeyCode::Backspace => {
+                    self.search.pop();
+                }
+                KeyCode::Esc => {
+                    self.search.clear();
+                    self.mode = Mode::Normal;
+                }
+                KeyCode::Enter => self.mode = Mode::Normal,
+                _ => {}
+            },
+            Mode::Input(input, buffer) => match key {
+                KeyCode::Char(c) => buffer.push(c),
+                KeyCode::Backspace => {
+                    buffer.pop();
+                }
+                KeyCode::Esc => self.mode = Mode::Normal,
+                KeyCode::Enter => {
+                    let (input, buffer) = (*input, buffer.to_owned());
+                    self.mode = Mode::Normal;
+                    self.submit(input, &buffer)?;
+                }
+                _ => {}
+            },
+            Mode::MoveTo(state) => match key {
+                KeyCode::Up | KeyCode::Char('k') => select_previous(state),
+                KeyCode::Down | KeyCode::Char('j') => {
+                    // the selected book is not proposed.
+                    select_next(state, self.books.len().saturating_sub(2))
+                }
+                KeyCode::Esc => self.mode = Mode::Normal,
+                KeyCode::Enter => {
+                    let index = state.selected();
+                    self.mode = Mode::Normal;
+                    let to = index.and_then(|i| self.other_books().get(i).cloned());
+                    if let (Some(to), Some(from), Some(contact)) = (
+                        to,
+                        self.selected_book().map(|b| b.name.clone()),
+                        self.selected_contact(),
+                    ) {
+                        let uuid = contact_uuid(contact)?;
+                        let remove = Change::RemoveFromBook { uuid, book: from };
+                        // the contact is not added to the other book if it can't leave this one.
+                        remove.check_access(store())?;
+                        self.stage(Change::AddToBook { uuid, book: to })?;
+                        self.stage(remove)?;
+                    }
+                }
+                _ => {}
+            },
+        }
+        Ok(())
+    }
+
+    fn on_key_normal(&mut self, key: KeyCode) -> Result<()> {
+        match key {
+            KeyCode::Char('q') if !self.pending.is_empty() => {
+                self.message = Some(
+                    "There are unwritten changes: w to write them, Q to quit without writing"
+                        .to_string(),
+                )
+            }
+            KeyCode::Char('q') | KeyCode::Char('Q') => self.quit = true,
+            KeyCode::Tab => {
+                self.focus = match self.focus {
+                    Focus::Books => Focus::Contacts,
+                    Focus::Contacts => Focus::Properties,
+                    Focus::Properties => Focus::Books,
+                }
+            }
+            KeyCode::BackTab => {
+                self.focus = match self.focus {
+                    Focus::Books => Focus::Properties,
+                    Focus::Contacts => Focus::Books,
+                    Focus::Properties => Focus::Contacts,
+                }
+            }
+            KeyCode::Up | KeyCode::Char('k') => self.move_selection(false),
+            KeyCode::Down | KeyCode::Char('j') => self.move_selection(true),
+            KeyCode::Char('/') => {
+                self.focus = Focus::Contacts;
+                self.contacts_state.select(Some(0));
+                self.mode = Mode::Search;
+            }
+            KeyCode::Char('a') => {
+                let input = if self.focus == Focus::Properties {
+                    Input::NewProperty
+                } else {
+                    Input::NewContact
+                };
+                self.mode = Mode::Input(input, String::new());
+            }
+            KeyCode::Char('e') if self.focus == Focus::Properties => {
+                if let Some(p) = self.selected_property() {
+                    check_modifiable(&p)?;
+                    self.mode = Mode::Input(Input::EditProperty, raw_property(&p));
+                }
+            }
+            KeyCode::Char('d') => self.delete()?,
+            KeyCode::Char('m') if self.focus == Focus::Contacts => {
+                if self.selected_contact().is_some() {
+                    self.mode = Mode::MoveTo(ListState::default().with_selected(Some(0)));
+                }
+            }
+            KeyCode::Char('w') => self.write()?,
+            KeyCode::Char('u') => {
+                if self.pending.pop().is_some() {
+                    self.reload()?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn move_selection(&mut self, next: bool) {
+        let (state, len) = match self.focus {
+            Focus::Books => (&mut self.books_state, self.books.len()),
+            Focus::Contacts => {
+                let len = self.visible_contacts().len();
+                (&mut self.contacts_state, len)
+            }
+            Focus::Properties => {
+                let len = self
+                    .selected_contact()
+                    .map(|c| c.get_properties().len())
+                    .unwrap_or_default();
+                (&mut self.properties_state, len)
+            }
+        };
+        if next {
+            select_next(state, len.saturating_sub(1))
+        } else {
+            select_previous(state)
+        }
+        match self.focus {
+            Focus::Books => {
+                self.contacts_state.select(Some(0));
+                self.properties_state.select(Some(0));
+            }
+            Focus::Contacts => self.properties_state.select(Some(0)),
+            Focus::Properties => {}
+        }
+    }
+
+    fn delete(&mut self) -> Result<()> {
+        let (Some(book), Some(contact)) = (
+            self.selected_book().map(|b| b.name.clone()),
+            self.selected_contact(),
+        ) else {
+            return Ok(());
+        };
+        let uuid = contact_uuid(contact)?;
+        match self.focus {
+            Focus::Contacts => self.stage(Change::RemoveFromBook { uuid, book }),
+            Focus::Properties => {
+                if let Some(property) = self.selected_property() {
+                    check_modifiable(&property)?;
+                    if property.name() == PropertyName::FN {
+                        bail!("the full name can not be deleted")
+                    }
+                    self.stage(Change::DeleteProperty { uuid, property })?;
+                }
+                Ok(())
+            }
+            Focus::Books => Ok(()),
+        }
+    }
+
+    fn submit(&mut self, input: Input, buffer: &str) -> Result<()> {
+        if buffer.trim().is_empty() {
+            return Ok(());
+        }
+        match input {
+            Input::NewContact => {
+                let Some(book) = self.selected_book().map(|b| b.name.clone()) else {
+                    bail!("no book selected")
+                };
+                let fullname = buffer.trim().to_string();
+                if self
+                    .books
+                    .iter()
+                    .flat_map(|b| b.contacts.iter())
+                    .filter_map(|c| c.get_property_by_name(PropertyName::FN))
+                    .any(|p| p.get_value().to_string() == fullname)
+                {
+                    bail!("a contact named {fullname} already exists")
+                }
+                self.stage(Change::CreateContact {
+                    uuid: Uuid::new_v4(),
+                    fullname,
+                    book,
+                })?;
+            }
+            Input::NewProperty | Input::EditProperty => {
+                let Some(contact) = self.selected_contact() else {
+                    return Ok(());
+                };
+                let uuid = contact_uuid(contact)?;
+                let new = Property::create_from_str(&format!("{}\n", buffer.trim()))?;
+                check_modifiable(&new)?;
+                // a property present once replaces the one of the contact.
+                let present = contact
+                    .get_properties()
+                    .into_iter()
+                    .find(|p| new.cardinality() != "MULTIPLE" && p.name() == new.name());
+                let change = match (input, self.selected_property(), present) {
+                    (Input::EditProperty, Some(old), _) | (_, _, Some(old)) => {
+                        Change::ModifyProperty { uuid, old, new }
+                    }
+                    _ => Change::AddProperty {
+                        uuid,
+                        property: new,
+                    },
+                };
+                self.stage(change)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// keep the modification to write it later and show it in the representation.
+    /// A modification refused by the access mode or a read-only book is not kept.
+    fn stage(&mut self, change: Change) -> Result<()> {
+        change.check_access(store())?;
+        apply_in_memory(&mut self.books, &change)?;
+        self.pending.push(change);
+        Ok(())
+    }
+
+    /// write every pending modification to the contacts.
+    fn write(&mut self) -> Result<()> {
+        let nb = self.pending.len();
+        // on error, the changes not written stay pending.
+        let written = self.pending.apply(store());
+        self.reload()?;
+        written?;
+        self.message = Some(format!("{nb} changes written"));
+        Ok(())
+    }
+
+    /// load again the books from the contacts and apply the pending modifications on them.
+    fn reload(&mut self) -> Result<()> {
+        self.books = load_books(&self.pending)?.0;
+        Ok(())
+    }
+}
+
+/// the full name can be modified but not deleted, other reserved properties can't be touched.
+fn check_modifiable(property: &Property) -> Result<()> {
+    if PROPERTY_NO_MODIFICATION_BY_USER.contains(&property.name()) {
+        bail!("{} can not be modified", property.name())
+    }
+    Ok(())
+}
+
+fn raw_property(property: &Property) -> String {
+    property.export().trim_end().to_string()
+}
+
+fn select_next(state: &mut ListState, last: usize) {
+    state.select(Some(state.selected().map_or(0, |i| (i + 1).min(last))));
+}
+
+fn select_previous(state: &mut ListState) {
+    state.select(Some(state.selected().map_or(0, |i| i.saturating_sub(1))));
+}
@@ -0,0 +1,32 @@
+mod app;
+mod ui;
+
+use anyhow::Result;
+use ratatui::crossterm::event::{self, Event, KeyEventKind};
+use ratatui::DefaultTerminal;
+
+use app::App;
+
+/// full screen interface, until the user quits.
+pub fn tui_mode() -> Result<()> {
+    let mut app = App::new()?;
+    let mut terminal = ratatui::init();
+    let result = run(&mut terminal, &mut app);
+    ratatui::restore();
+    result
+}
+
+fn run(terminal: &mut DefaultTerminal, app: &mut App) -> Result<()> {
+    while !app.quit {
+        terminal.draw(|frame| ui::draw(frame, app))?;
+        if let Event::Key(key) = event::read()? {
+            if key.kind == KeyEventKind::Press {
+                // errors are shown to the user instead of leaving the interface.
+                if let Err(e) = app.on_key(key.code) {
+                    app.message = Some(e.to_string());
+                }
+            }
+        }
+    }
+    Ok(())
+}
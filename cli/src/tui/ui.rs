@@ -0,0 +1,166 @@
+use std::str::FromStr;
+
+use promptable::basics::display::PromptableDisplay;
+use ratatui::{
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::Line,
+    widgets::{Block, Clear, List, ListItem, Paragraph},
+    Frame,
+};
+
+use super::app::{App, Focus, Mode};
+use crate::interactive::{book::tree_name, contact_uuid, menu::PropertyWrapper};
+
+pub fn draw(frame: &mut Frame, app: &mut App) {
+    let [main, status] =
+        Layout::vertical([Constraint::Fill(1), Constraint::Length(3)]).areas(frame.area());
+    let [books, contacts, detail] = Layout::horizontal([
+        Constraint::Percentage(20),
+        Constraint::Percentage(30),
+        Constraint::Percentage(50),
+    ])
+    .areas(main);
+    draw_books(frame, app, books);
+    draw_contacts(frame, app, contacts);
+    draw_detail(frame, app, detail);
+    draw_status(frame, app, status);
+    if matches!(app.mode, Mode::MoveTo(_)) {
+        draw_move_to(frame, app, main);
+    }
+}
+
+fn block(title: String, focused: bool) -> Block<'static> {
+    let style = if focused {
+        Style::new().fg(Color::Yellow)
+    } else {
+        Style::new()
+    };
+    Block::bordered().title(title).border_style(style)
+}
+
+fn list<T: Into<ListItem<'static>>>(items: Vec<T>, block: Block<'static>) -> List<'static> {
+    List::new(items)
+        .block(block)
+        .highlight_style(Style::new().add_modifier(Modifier::REVERSED))
+        .highlight_symbol("> ")
+}
+
+fn draw_books(frame: &mut Frame, app: &mut App, area: Rect) {
+    let items: Vec<ListItem<'static>> = app
+        .books
+        .iter()
+        .map(|b| {
+            let item = ListItem::new(format!("{} ({})", tree_name(&b.name), b.contacts.len()));
+            // the color chosen for the book, if it is a valid one.
+            match b
+                .info
+                .color
+                .as_deref()
+                .and_then(|color| Color::from_str(color).ok())
+            {
+                Some(color) => item.style(Style::new().fg(color)),
+                None => item,
+            }
+        })
+        .collect();
+    let block = block("Books".to_string(), app.focus == Focus::Books);
+    frame.render_stateful_widget(list(items, block), area, &mut app.books_state);
+}
+
+fn draw_contacts(frame: &mut Frame, app: &mut App, area: Rect) {
+    let items: Vec<String> = app
+        .visible_contacts()
+        .iter()
+        .map(|c| c.display_short())
+        .collect();
+    let title = if app.search.is_empty() {
+        "Contacts".to_string()
+    } else {
+        format!("Contacts /{}", app.search)
+    };
+    let block = block(title, app.focus == Focus::Contacts);
+    frame.render_stateful_widget(list(items, block), area, &mut app.contacts_state);
+}
+
+fn draw_detail(frame: &mut Frame, app: &mut App, area: Rect) {
+    let focused = app.focus == Focus::Properties;
+    let Some(contact) = app.selected_contact() else {
+        frame.render_widget(block("Contact".to_string(), focused), area);
+        return;
+    };
+    let books = contact_uuid(contact)
+        .map(|uuid| app.books_of(&uuid).join(", "))
+        .unwrap_or_default();
+    let title = format!("{} [{}]", contact.display_short(), books);
+    let items: Vec<String> = contact
+        .get_properties()
+        .into_iter()
+        .map(|p| PropertyWrapper(p).display_short())
+        .collect();
+    let block = block(title, focused);
+    frame.render_stateful_widget(list(items, block), area, &mut app.properties_state);
+}
+
+fn draw_status(frame: &mut Frame, app: &App, area: Rect) {
+    let pending = if app.pending.is_empty() {
+        Line::from("No pending changes")
+    } else {
+        Line::styled(
+            format!(
+                "● {} pending changes, last: {} (w: write, u: undo)",
+                app.pending.len(),
+                app.pending
+                    .changes()
+                    .last()
+                    .map(|c| c.to_string().replace('\n', " "))
+                    .unwrap_or_default()
+            ),
+            Style::new().fg(Color::Red),
+        )
+    };
+    let input = match &app.mode {
+        Mode::Search => Line::from(format!("/{}", app.search)),
+        Mode::Input(input, buffer) => Line::from(format!("{input}{buffer}")),
+        Mode::MoveTo(_) => Line::from("Enter: move the contact to the book, Esc: cancel"),
+        Mode::Normal => match &app.message {
+            Some(message) => Line::from(message.as_str()),
+            None => Line::from(match app.focus {
+                Focus::Books => "Tab: next pane, a: add contact, /: search, q: quit",
+                Focus::Contacts => {
+                    "Tab: next pane, a: add, d: remove from book, m: move to book, /: search, q: quit"
+                }
+                Focus::Properties => "Tab: next pane, a: add, e: edit, d: delete, q: quit",
+            }),
+        },
+    };
+    frame.render_widget(
+        Paragraph::new(vec![pending, input]).block(Block::bordered()),
+        area,
+    );
+}
+
+fn draw_move_to(frame: &mut Frame, app: &mut App, area: Rect) {
+    let books = app.other_books();
+    let Mode::MoveTo(state) = &mut app.mode else {
+        return;
+    };
+    let popup = centered(area, 40, books.len() as u16 + 2);
+    frame.render_widget(Clear, popup);
+    frame.render_stateful_widget(
+        list(books, block("Move to".to_string(), true)),
+        popup,
+        state,
+    );
+}
+
+fn centered(area: Rect, width: u16, height: u16) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    }
+}
@@ -1,10 +1,13 @@
 #[cfg(test)]
 mod tests {
-    use contact_manager_lib::vcard_parser::{
-        traits::HasValue,
-        vcard::{
-            property::{property_adr::PropertyAdrData, Property},
-            value::{value_listcomponent::ValueListComponentData, Value},
+    use contact_manager_lib::{
+        address::Address,
+        vcard_parser::{
+            traits::HasValue,
+            vcard::{
+                property::{property_adr::PropertyAdrData, Property},
+                value::{value_listcomponent::ValueListComponentData, Value},
+            },
         },
     };
 
@@ -21,4 +24,57 @@ mod tests {
 
         // assert!(!property_match(&p_filter, &p_vcard, false).unwrap());
     }
+
+    #[test]
+    fn adr_round_trip() {
+        let raws = [
+            ";43;Rue de la Maladière;Dijon;Bourgogne;21000;France",
+            ";Apt 4B;10 Downing Street;London;;SW1A 2AA;United Kingdom",
+            ";;1 Main Street,Building B;Ottawa;ON;K1A 0B1;Canada",
+            "PO 12;;Keizersgracht 1\\, achterhuis;Amsterdam;;1015 CJ;Netherlands",
+        ];
+        for raw in raws {
+            assert_eq!(Address::from_value(raw).to_value(), raw);
+        }
+        let address = Address::from_value(raws[2]);
+        assert_eq!(address.street, vec!["1 Main Street", "Building B"]);
+        assert_eq!(address.postal_code, vec!["K1A 0B1"]);
+        let address = Address::from_value(raws[3]);
+        assert_eq!(address.street, vec!["Keizersgracht 1, achterhuis"]);
+    }
+
+    #[test]
+    fn adr_round_trip_property() {
+        for raw in [
+            ";Apt 4B;10 Downing Street;London;;SW1A 2AA;United Kingdom",
+            // an escaped comma inside a value and two values in a component.
+            ";Apt 4B\\, 2nd floor;10 Downing Street,Westminster;London;;SW1A 2AA;United Kingdom",
+        ] {
+            let mut property = Property::PropertyAdr(PropertyAdrData::default());
+            property
+                .set_value(Address::from_value(raw).to_property_value().unwrap())
+                .unwrap();
+            assert_eq!(Address::from_property(&property).unwrap().to_value(), raw);
+        }
+        let address = Address::from_value(";Apt 4B\\, 2nd floor;;;;;");
+        assert_eq!(address.extended, ["Apt 4B, 2nd floor"]);
+    }
+
+    #[test]
+    fn adr_lines() {
+        let address = Address::from_value(";;10 Downing Street;London;;SW1A 2AA;United Kingdom");
+        assert_eq!(
+            address.lines(),
+            vec!["10 Downing Street", "London SW1A 2AA", "United Kingdom"]
+        );
+        let address = Address::from_value(";43;Rue de la Maladière;Dijon;;21000;France");
+        assert_eq!(
+            address.lines(),
+            vec!["43", "Rue de la Maladière", "21000 Dijon", "France"]
+        );
+        assert_eq!(
+            address.label(),
+            "43\\nRue de la Maladière\\n21000 Dijon\\nFrance"
+        );
+    }
 }
@@ -0,0 +1,26 @@
+// every test file uses only a part of the helpers.
+#![allow(dead_code)]
+
+use contact_manager_lib::{
+    uuid::Uuid,
+    vcard_parser::vcard::{property::Property, Vcard},
+};
+
+/// a contact with the full name and the properties, written like in a vcard file.
+pub fn vcard(fullname: &str, properties: &[&str]) -> Vcard {
+    let mut vcard = Vcard::new(fullname);
+    for p in properties {
+        vcard
+            .set_property(&Property::create_from_str(&format!("{p}\n")).unwrap())
+            .unwrap();
+    }
+    vcard
+}
+
+/// a contact with the full name, the properties and a new uid.
+pub fn vcard_with_uid(fullname: &str, properties: &[&str]) -> Vcard {
+    let uid = format!("UID:{}", Uuid::new_v4());
+    let mut properties = properties.to_vec();
+    properties.push(&uid);
+    vcard(fullname, &properties)
+}
@@ -0,0 +1,72 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use contact_manager_lib::{
+        changes::Change,
+        replace_changes, replace_pattern,
+        vcard_parser::{
+            traits::{HasParameters, HasValue},
+            vcard::{property::Property, Vcard},
+        },
+    };
+
+    fn vcard(properties: &[&str]) -> Vcard {
+        crate::common::vcard_with_uid("Jean Dupont", properties)
+    }
+
+    /// the new values of the modified properties.
+    fn replaced(vcard: &Vcard, name: &str, from: &str, to: &str, regex: bool) -> Vec<Property> {
+        let (pattern, to) = replace_pattern(from, to, regex).unwrap();
+        replace_changes(&[vcard.clone()], name, &pattern, &to)
+            .unwrap()
+            .changes()
+            .iter()
+            .filter_map(|change| match change {
+                Change::ModifyProperty { new, .. } => Some(new.to_owned()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn replace_literal_or_regex() {
+        let vcard = vcard(&["EMAIL:jean.dupont@old.com"]);
+        // the dot matches any character only in a regular expression.
+        assert!(replaced(&vcard, "EMAIL", "jean.dupont@old.co.", "x", false).is_empty());
+        let new = replaced(&vcard, "email", r"@old\.com$", "@new.com", true);
+        assert_eq!(new[0].get_value().to_string(), "jean.dupont@new.com");
+        let new = replaced(&vcard, "EMAIL", "(\\w+)\\.(\\w+)@", "$2.$1@", true);
+        assert_eq!(new[0].get_value().to_string(), "dupont.jean@old.com");
+    }
+
+    #[test]
+    fn replace_dollar() {
+        let vcard = vcard(&["NOTE:price in euros"]);
+        // without --regex, "$1" is written as is.
+        let new = replaced(&vcard, "NOTE", "euros", "$1", false);
+        assert_eq!(new[0].get_value().to_string(), "price in $1");
+    }
+
+    #[test]
+    fn replace_parameters_kept() {
+        let vcard = vcard(&["EMAIL;TYPE=work;PID=1.1:jean@old.com"]);
+        let new = replaced(&vcard, "EMAIL", "old", "new", false);
+        assert_eq!(new[0].get_value().to_string(), "jean@new.com");
+        let old = vcard.get_property_by_name("EMAIL").unwrap();
+        assert_eq!(new[0].get_parameters(), old.get_parameters());
+    }
+
+    #[test]
+    fn replace_unescaped() {
+        // the escaped comma is matched as a comma, the separators of the components are kept.
+        let vcard = vcard(&["ADR:;;1 rue de Paris\\, bat A;Lyon;;69001;France"]);
+        let new = replaced(&vcard, "ADR", ", bat A", "", false);
+        assert_eq!(
+            new[0].get_value().to_string(),
+            ";;1 rue de Paris;Lyon;;69001;France"
+        );
+        // a pattern can not match across components.
+        assert!(replaced(&vcard, "ADR", "Lyon;", "", false).is_empty());
+    }
+}
@@ -0,0 +1,78 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::OnceLock;
+
+    use contact_manager_lib::{
+        access::Store,
+        add_or_replace_property, create_book, create_contact, export, generate_index,
+        paths::set_data_directory,
+        smart::{save_smart_book, SmartBook},
+        uuid::Uuid,
+        vcard_parser::{traits::HasValue, vcard::property::Property},
+        vcards_from_book,
+    };
+
+    const SMART: &str = "@lyon";
+
+    /// a store in a new directory, with a contact of Lyon and one of Paris in two books,
+    /// and a smart book finding the contacts of Lyon in every book.
+    fn store() -> &'static Store {
+        static STORE: OnceLock<Store> = OnceLock::new();
+        STORE.get_or_init(|| {
+            let directory = std::env::temp_dir().join(format!("cm-tests-{}", Uuid::new_v4()));
+            set_data_directory(directory).unwrap();
+            let store = Store::new("cm-tests");
+            for (book, fullname, email) in [
+                ("friends", "Jean Dupont", "EMAIL:jean@lyon.fr"),
+                ("work", "Paul Martin", "EMAIL:paul@paris.fr"),
+            ] {
+                create_book(book, &store).unwrap();
+                let uuids = create_contact(&store, book, &vec![fullname.to_string()]).unwrap();
+                let email = Property::create_from_str(&format!("{email}\n")).unwrap();
+                add_or_replace_property(&store, &vec![&email], &uuids).unwrap();
+            }
+            let query = SmartBook {
+                filters: vec!["EMAIL:@lyon.fr".to_string()],
+                forgive: true,
+                ..Default::default()
+            };
+            save_smart_book(&store, SMART, &query).unwrap();
+            store
+        })
+    }
+
+    #[test]
+    fn smart_book_read() {
+        let vcards = vcards_from_book(store(), Some(SMART), false).unwrap();
+        assert_eq!(vcards.len(), 1);
+        assert_eq!(
+            vcards[0]
+                .get_property_by_name("FN")
+                .unwrap()
+                .get_value()
+                .to_string(),
+            "Jean Dupont"
+        );
+    }
+
+    #[test]
+    fn smart_book_export() {
+        let exported = export(Some(SMART), false, store()).unwrap();
+        assert!(exported.contains("Jean Dupont"));
+        assert!(!exported.contains("Paul Martin"));
+    }
+
+    #[test]
+    fn smart_book_index() {
+        let properties = vec![Property::default("EMAIL"), Property::default("FN")];
+        let index = generate_index(store(), Some(SMART), &properties).unwrap();
+        assert_eq!(index.len(), 1);
+        assert!(index[0].contains("jean@lyon.fr"));
+        assert!(index[0].contains("Jean Dupont"));
+    }
+
+    #[test]
+    fn smart_book_is_read_only() {
+        assert!(create_contact(store(), SMART, &vec!["Marie".to_string()]).is_err());
+    }
+}
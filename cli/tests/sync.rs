@@ -0,0 +1,143 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use contact_manager_lib::{
+        sync::{assign_pids, known_uuid, merge},
+        uuid::Uuid,
+        vcard_parser::{
+            traits::{HasName, HasValue},
+            vcard::{property::Property, Vcard},
+        },
+    };
+
+    const SOURCE: &str = "urn:uuid:53e374d9-337e-4727-8803-a1e9c14e0556";
+
+    fn vcard(properties: &[&str]) -> Vcard {
+        crate::common::vcard("Jean Dupont", properties)
+    }
+
+    fn values(vcard: &Vcard, name: &str) -> Vec<String> {
+        let mut values: Vec<String> = vcard
+            .get_properties()
+            .iter()
+            .filter(|p| p.name() == name)
+            .map(|p| p.get_value().to_string())
+            .collect();
+        values.sort();
+        values
+    }
+
+    /// the version given by the source at the first import, which is also the stored contact.
+    fn first_import() -> Vcard {
+        let mut previous = vcard(&[
+            &format!("CLIENTPIDMAP:1;{SOURCE}"),
+            "EMAIL;PID=1.1:jean@example.com",
+            "BDAY:19800101",
+        ]);
+        assign_pids(&mut previous, SOURCE, None).unwrap();
+        previous
+    }
+
+    fn import(properties: &[&str], previous: &Vcard) -> Vcard {
+        let mut remote = vcard(properties);
+        assign_pids(&mut remote, SOURCE, Some(previous)).unwrap();
+        remote
+    }
+
+    #[test]
+    fn remote_only_edit() {
+        let previous = first_import();
+        let local = previous.clone();
+        let remote = import(&["EMAIL:jean.dupont@example.com", "BDAY:19800101"], &previous);
+        let merged = merge(&local, &remote, Some(&previous), SOURCE).unwrap();
+        assert_eq!(values(&merged, "EMAIL"), ["jean.dupont@example.com"]);
+        // the source kept the PIDs of the previous version.
+        let remote = import(
+            &[
+                &format!("CLIENTPIDMAP:1;{SOURCE}"),
+                "EMAIL;PID=1.1:jean.dupont@example.com",
+                "BDAY:19800101",
+            ],
+            &previous,
+        );
+        let merged = merge(&local, &remote, Some(&previous), SOURCE).unwrap();
+        assert_eq!(values(&merged, "EMAIL"), ["jean.dupont@example.com"]);
+    }
+
+    #[test]
+    fn local_only_edit() {
+        let previous = first_import();
+        let mut local = previous.clone();
+        let email = local.get_property_by_name("EMAIL").unwrap();
+        local.remove_property(&email).unwrap();
+        local
+            .set_property(&Property::create_from_str("EMAIL;PID=1.1:jd@example.com\n").unwrap())
+            .unwrap();
+        let remote = import(&["EMAIL:jean@example.com", "BDAY:19800101"], &previous);
+        let merged = merge(&local, &remote, Some(&previous), SOURCE).unwrap();
+        assert_eq!(values(&merged, "EMAIL"), ["jd@example.com"]);
+    }
+
+    #[test]
+    fn both_sides_edited() {
+        let previous = first_import();
+        let mut local = previous.clone();
+        for (name, new) in [
+            ("EMAIL", "EMAIL;PID=1.1:jd@example.com"),
+            ("BDAY", "BDAY:19800102"),
+        ] {
+            let old = local.get_property_by_name(name).unwrap();
+            local.remove_property(&old).unwrap();
+            local
+                .set_property(&Property::create_from_str(&format!("{new}\n")).unwrap())
+                .unwrap();
+        }
+        local
+            .set_property(&Property::create_from_str("TEL:+33123456789\n").unwrap())
+            .unwrap();
+        let remote = import(
+            &[
+                &format!("CLIENTPIDMAP:1;{SOURCE}"),
+                "EMAIL;PID=1.1:jean.dupont@example.com",
+                "BDAY:19800103",
+                "TITLE:Engineer",
+            ],
+            &previous,
+        );
+        let merged = merge(&local, &remote, Some(&previous), SOURCE).unwrap();
+        // both versions of a property present multiple times are kept, the local one for the others.
+        assert_eq!(
+            values(&merged, "EMAIL"),
+            ["jd@example.com", "jean.dupont@example.com"]
+        );
+        assert_eq!(values(&merged, "BDAY"), ["19800102"]);
+        // the properties added on each side are kept.
+        assert_eq!(values(&merged, "TEL"), ["+33123456789"]);
+        assert_eq!(values(&merged, "TITLE"), ["Engineer"]);
+    }
+
+    #[test]
+    fn deletions() {
+        let previous = first_import();
+        // deleted by the source.
+        let remote = import(&["BDAY:19800101"], &previous);
+        let merged = merge(&previous, &remote, Some(&previous), SOURCE).unwrap();
+        assert!(values(&merged, "EMAIL").is_empty());
+        // deleted locally, the source did not modify it.
+        let mut local = previous.clone();
+        let email = local.get_property_by_name("EMAIL").unwrap();
+        local.remove_property(&email).unwrap();
+        let remote = import(&["EMAIL:jean@example.com", "BDAY:19800101"], &previous);
+        let merged = merge(&local, &remote, Some(&previous), SOURCE).unwrap();
+        assert!(values(&merged, "EMAIL").is_empty());
+    }
+
+    #[test]
+    fn missing_uid() {
+        let uuid = Uuid::new_v4();
+        let previous = vcard(&[&format!("UID:{uuid}")]);
+        assert_eq!(known_uuid(&vcard(&[]), &[previous.clone()]), Some(uuid));
+        assert_eq!(known_uuid(&Vcard::new("Paul"), &[previous]), None);
+    }
+}
@@ -0,0 +1,79 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use contact_manager_lib::{
+        template::{template_changes, Template},
+        uuid::Uuid,
+        vcard_parser::{traits::HasValue, vcard::Vcard},
+    };
+
+    use crate::common::vcard_with_uid as vcard;
+
+    fn render(template: &str, vcard: &Vcard) -> Option<String> {
+        Template::display(template)
+            .unwrap()
+            .render_value(vcard)
+            .unwrap()
+    }
+
+    #[test]
+    fn template_parse() {
+        assert!(Template::parse("EMAIL:{N.given}@corp.com").is_ok());
+        // the colon of a quoted parameter does not start the value.
+        let template = Template::parse("EMAIL;LABEL=\"a:b\":{FN}").unwrap();
+        let property = template
+            .render(&vcard("Jean Dupont", &[]))
+            .unwrap()
+            .unwrap();
+        assert_eq!(property.get_value().to_string(), "Jean Dupont");
+        assert!(Template::parse("EMAIL").is_err());
+        assert!(Template::parse("EMAIL:{FN").is_err());
+        assert!(Template::parse("EMAIL:FN}").is_err());
+        assert!(Template::parse("EMAIL:{}").is_err());
+        assert!(Template::parse("EMAIL:{FN|reverse}").is_err());
+        assert!(Template::parse("EMAIL:{N.middle}").is_err());
+    }
+
+    #[test]
+    fn template_components() {
+        let vcard = vcard("Jean Dupont", &["N:Dupont;Jean;;;"]);
+        assert_eq!(render("{N.given}", &vcard).unwrap(), "Jean");
+        assert_eq!(render("{n.GIVEN}", &vcard).unwrap(), "Jean");
+        assert_eq!(render("{N.1}", &vcard).unwrap(), "Jean");
+        assert_eq!(render("{N.family}", &vcard).unwrap(), "Dupont");
+        assert_eq!(render("{{N.given}}", &vcard).unwrap(), "{N.given}");
+        // a property absent from the contact.
+        assert_eq!(render("{ORG}", &vcard), None);
+    }
+
+    #[test]
+    fn template_filters() {
+        let vcard = vcard("jean-luc DUPONT", &["N:Dupont;Jean-Luc;;;"]);
+        assert_eq!(render("{FN|lower}", &vcard).unwrap(), "jean-luc dupont");
+        assert_eq!(render("{FN|upper}", &vcard).unwrap(), "JEAN-LUC DUPONT");
+        assert_eq!(render("{FN|capitalize}", &vcard).unwrap(), "Jean-Luc Dupont");
+        assert_eq!(
+            render("{N.given|lower}.{N.family|lower}@corp.com", &vcard).unwrap(),
+            "jean-luc.dupont@corp.com"
+        );
+    }
+
+    #[test]
+    fn template_changes_skip() {
+        let template = Template::parse("EMAIL;TYPE=work:{N.given|lower}@corp.com").unwrap();
+        let with_n = vcard("Jean Dupont", &["N:Dupont;Jean;;;"]);
+        let without_n = vcard("Paul", &[]);
+        let uuid_without = Uuid::parse_str(
+            &without_n
+                .get_property_by_name("UID")
+                .unwrap()
+                .get_value()
+                .to_string(),
+        )
+        .unwrap();
+        let (changes, skipped) = template_changes(&[with_n, without_n], &template).unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(skipped, [uuid_without]);
+    }
+}
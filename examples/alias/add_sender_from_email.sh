@@ -0,0 +1,5 @@
+#!/bin/bash
+
+# to use in neomutt, with a macro piping the message:
+# macro index,pager A "<pipe-message>cm add-from-email -y<enter>" "add the sender to the contacts"
+cm add-from-email --yes
@@ -0,0 +1,144 @@
+use std::{fmt::Display, fs, ops::Deref};
+
+use uuid::Uuid;
+
+use crate::{
+    error::ErrorContactManager, find_books_where_contact_is_present, paths::book_directory,
+    smart::is_smart_book,
+};
+
+/// name of the file marking a book as read-only, inside the directory of the book.
+pub const READ_ONLY_MARKER: &str = ".read-only";
+
+/// what the functions of the library are allowed to do on the contacts.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum AccessMode {
+    /// contacts can be read and modified.
+    #[default]
+    ReadWrite,
+    /// contacts can only be read, every modification returns an error.
+    ReadOnly,
+    /// contacts can only be created or modified, every reading returns an error.
+    WriteOnly,
+}
+
+/// the contacts of an application, given to the functions of the library with what they are allowed to do on them.
+/// It can be given where the name of the application is expected.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Store {
+    app_name: String,
+    mode: AccessMode,
+}
+
+impl Store {
+    /// the contacts of the application, which can be read and modified.
+    pub fn new(app_name: &str) -> Self {
+        Store {
+            app_name: app_name.to_string(),
+            mode: AccessMode::default(),
+        }
+    }
+    /// the same contacts with another access mode.
+    pub fn with_mode(mut self, mode: AccessMode) -> Self {
+        self.mode = mode;
+        self
+    }
+    /// name of the application.
+    pub fn app_name(&self) -> &str {
+        &self.app_name
+    }
+    /// the access mode of the contacts.
+    pub fn mode(&self) -> AccessMode {
+        self.mode
+    }
+}
+
+impl Deref for Store {
+    type Target = str;
+    fn deref(&self) -> &str {
+        &self.app_name
+    }
+}
+
+impl Display for Store {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.app_name)
+    }
+}
+
+/// mark a book as read-only, or make it writable again.
+pub fn set_book_read_only(
+    store: &Store,
+    book_name: &str,
+    read_only: bool,
+) -> Result<(), ErrorContactManager> {
+    check_write(store)?;
+    let marker = book_directory(book_name, store)?.join(READ_ONLY_MARKER);
+    if read_only {
+        fs::write(marker, "")?;
+    } else if marker.exists() {
+        fs::remove_file(marker)?;
+    }
+    Ok(())
+}
+
+/// true if the book, or a book it is nested in, has been marked as read-only.
+/// Smart books are always read-only.
+pub fn is_book_read_only(app_name: &str, book_name: &str) -> Result<bool, ErrorContactManager> {
+    if is_smart_book(book_name) {
+        return Ok(true);
+    }
+    let mut name = book_name;
+    loop {
+        if book_directory(name, app_name)?
+            .join(READ_ONLY_MARKER)
+            .exists()
+        {
+            return Ok(true);
+        }
+        match name.rsplit_once('/') {
+            Some((parent, _)) => name = parent,
+            None => return Ok(false),
+        }
+    }
+}
+
+/// return an error if the contacts can't be read.
+pub(crate) fn check_read(store: &Store) -> Result<(), ErrorContactManager> {
+    if store.mode == AccessMode::WriteOnly {
+        return Err(ErrorContactManager::WriteOnly);
+    }
+    Ok(())
+}
+
+/// return an error if the contacts can't be modified.
+pub(crate) fn check_write(store: &Store) -> Result<(), ErrorContactManager> {
+    if store.mode == AccessMode::ReadOnly {
+        return Err(ErrorContactManager::ReadOnly);
+    }
+    Ok(())
+}
+
+/// return an error if the book can't be modified.
+pub(crate) fn check_write_book(store: &Store, book_name: &str) -> Result<(), ErrorContactManager> {
+    check_write(store)?;
+    if is_book_read_only(store, book_name)? {
+        return Err(ErrorContactManager::ReadOnlyBook(book_name.to_string()));
+    }
+    Ok(())
+}
+
+/// return an error if one of the contacts can't be modified, because it is present in a read-only book.
+pub(crate) fn check_write_contacts(
+    store: &Store,
+    uuids: &Vec<Uuid>,
+) -> Result<(), ErrorContactManager> {
+    check_write(store)?;
+    for uuid in uuids {
+        for book in find_books_where_contact_is_present(uuid, store)? {
+            check_write_book(store, &book)?;
+        }
+    }
+    Ok(())
+}
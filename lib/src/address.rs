@@ -0,0 +1,204 @@
+use vcard_parser::{
+    constants::PropertyName,
+    traits::{HasName, HasValue},
+    vcard::{
+        property::Property,
+        value::{value_listcomponent::ValueListComponentData, Value},
+    },
+};
+
+use crate::error::ErrorContactManager;
+
+/// countries writing the postal code after the locality and the region, by ISO code or name.
+const POSTAL_CODE_LAST: [&str; 10] = [
+    "US",
+    "United States",
+    "USA",
+    "CA",
+    "Canada",
+    "AU",
+    "Australia",
+    "GB",
+    "United Kingdom",
+    "UK",
+];
+
+/// the components of the property ADR, each one can have several values.
+/// Values are kept as written, so an address read then written is unchanged.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Address {
+    /// post office box.
+    pub post_office_box: Vec<String>,
+    /// extended address, like an apartment or suite number.
+    pub extended: Vec<String>,
+    /// street address.
+    pub street: Vec<String>,
+    /// locality, like a city.
+    pub locality: Vec<String>,
+    /// region, like a state or a province.
+    pub region: Vec<String>,
+    /// postal code, which can contain letters, like "SW1A 1AA".
+    pub postal_code: Vec<String>,
+    /// country name.
+    pub country: Vec<String>,
+}
+
+impl Address {
+    /// read the value of an ADR property as written in a vcard, example: ";Apt 4B;10 Downing Street;London;;SW1A 2AA;United Kingdom".
+    /// Missing components are empty.
+    pub fn from_value(raw: &str) -> Address {
+        let mut components = split_unescaped(raw, ';')
+            .into_iter()
+            .map(|component| split_values(&component));
+        let mut next = || components.next().unwrap_or_default();
+        Address {
+            post_office_box: next(),
+            extended: next(),
+            street: next(),
+            locality: next(),
+            region: next(),
+            postal_code: next(),
+            country: next(),
+        }
+    }
+    /// read an ADR property.
+    pub fn from_property(property: &Property) -> Result<Address, ErrorContactManager> {
+        if property.name() != PropertyName::ADR {
+            return Err(ErrorContactManager::Address(property.name().to_string()));
+        }
+        Ok(Address::from_value(&property.get_value().to_string()))
+    }
+    /// the value as written in a vcard, the separators in values being escaped.
+    pub fn to_value(&self) -> String {
+        self.components()
+            .iter()
+            .map(|values| join_values(values))
+            .collect::<Vec<String>>()
+            .join(";")
+    }
+    /// the value to set to an ADR property.
+    pub fn to_property_value(&self) -> Result<Value, ErrorContactManager> {
+        Ok(Value::try_from(ValueListComponentData::try_from((
+            self.to_value().as_str(),
+            ';',
+            ',',
+        ))?)?)
+    }
+    fn components(&self) -> [&Vec<String>; 7] {
+        [
+            &self.post_office_box,
+            &self.extended,
+            &self.street,
+            &self.locality,
+            &self.region,
+            &self.postal_code,
+            &self.country,
+        ]
+    }
+    /// true if every component is empty.
+    pub fn is_empty(&self) -> bool {
+        self.components().iter().all(|values| values.is_empty())
+    }
+    /// the lines of the address as written on an envelope, in the usage of the country.
+    /// The postal code is written before the locality, except for countries like the United States or the United Kingdom.
+    pub fn lines(&self) -> Vec<String> {
+        let join = |values: &Vec<String>| values.join(", ");
+        let mut lines = Vec::new();
+        if !self.post_office_box.is_empty() {
+            lines.push(format!("PO Box {}", join(&self.post_office_box)));
+        }
+        lines.extend(self.extended.iter().cloned());
+        lines.extend(self.street.iter().cloned());
+        let postal_code_last = self
+            .country
+            .iter()
+            .any(|c| POSTAL_CODE_LAST.iter().any(|l| l.eq_ignore_ascii_case(c.trim())));
+        let locality = if postal_code_last {
+            let locality = [join(&self.locality), join(&self.region)]
+                .into_iter()
+                .filter(|s| !s.is_empty())
+                .collect::<Vec<String>>()
+                .join(", ");
+            [locality, join(&self.postal_code)].join(" ")
+        } else {
+            let locality = [join(&self.postal_code), join(&self.locality)].join(" ");
+            if self.region.is_empty() {
+                locality
+            } else {
+                format!("{}\n{}", locality.trim(), join(&self.region))
+            }
+        };
+        lines.extend(
+            locality
+                .lines()
+                .map(|l| l.trim().to_string())
+                .filter(|l| !l.is_empty()),
+        );
+        lines.extend(self.country.iter().cloned());
+        lines
+    }
+    /// the address formatted for the LABEL parameter, lines separated by "\n" as written in a vcard.
+    pub fn label(&self) -> String {
+        self.lines().join("\\n")
+    }
+}
+
+/// read the values of one component, separated by commas, the escaped characters being unescaped.
+pub fn split_values(component: &str) -> Vec<String> {
+    if component.is_empty() {
+        return Vec::new();
+    }
+    split_unescaped(component, ',')
+        .iter()
+        .map(|value| unescape(value))
+        .collect()
+}
+
+/// write the values of one component, separated by commas, the separators in values being escaped.
+pub fn join_values(values: &[String]) -> String {
+    values
+        .iter()
+        .map(|value| escape(value))
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+/// split on the separator when it is not escaped, keeping the escapes.
+pub(crate) fn split_unescaped(raw: &str, separator: char) -> Vec<String> {
+    let mut parts = vec![String::new()];
+    let mut escaped = false;
+    for c in raw.chars() {
+        if c == separator && !escaped {
+            parts.push(String::new());
+        } else {
+            parts.last_mut().expect("never empty").push(c);
+        }
+        escaped = c == '\\' && !escaped;
+    }
+    parts
+}
+
+fn unescape(value: &str) -> String {
+    let mut unescaped = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => unescaped.push('\n'),
+            Some(next) => unescaped.push(next),
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(',', "\\,")
+        .replace(';', "\\;")
+        .replace('\n', "\\n")
+}
@@ -55,16 +55,23 @@ pub fn cardinals() -> (Vec<Property>, Vec<Property>) {
 /// generate a property with a new uid
 pub fn generate_uid_property() -> Result<(Property, Uuid), ErrorContactManager> {
     let uuid = Uuid::new_v4();
+    Ok((uid_property(&uuid)?, uuid))
+}
+
+/// the UID property of a contact.
+pub fn uid_property(uuid: &Uuid) -> Result<Property, ErrorContactManager> {
     let mut property_uuid = Property::PropertyUid(PropertyUidData::default());
     property_uuid.set_value(Value::ValueUri(
         vcard_parser::vcard::value::value_uri::ValueUriData {
             value: uuid.to_string(),
         },
     ))?;
-    Ok((property_uuid, uuid))
+    Ok(property_uuid)
 }
 
-trait HasConst {
+/// list every constant of a type, for example every standard property name.
+pub trait HasConst {
+    /// the constants as strings.
     fn to_strings() -> Vec<String>;
 }
 impl HasConst for PropertyName {
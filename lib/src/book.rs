@@ -0,0 +1,134 @@
+use std::fs;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use vcard_parser::{
+    constants::PropertyName,
+    traits::HasValue,
+    vcard::{property::Property, Vcard},
+};
+
+use crate::{
+    access::{check_read, check_write_book, Store},
+    error::ErrorContactManager,
+    paths::book_directory,
+    store::{lock_store, write_atomic},
+};
+
+/// name of the file containing the information of a book, inside the directory of the book.
+pub const BOOK_INFO_FILE: &str = ".book.toml";
+
+/// information about a book, stored with the book.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BookInfo {
+    /// what the book is used for.
+    pub description: Option<String>,
+    /// color to display the book, a name like "red" or an hex code like "#ff0000".
+    pub color: Option<String>,
+    /// categories given to the contacts created in the book.
+    pub categories: Vec<String>,
+    /// name of the property used to sort the contacts of the book, example: "FN" or "N", in any case.
+    pub sort_by: Option<String>,
+}
+
+/// read the information of a book. A book without information has the default one.
+pub fn book_info(app_name: &str, book_name: &str) -> Result<BookInfo, ErrorContactManager> {
+    let path = book_directory(book_name, app_name)?.join(BOOK_INFO_FILE);
+    if !path.exists() {
+        return Ok(BookInfo::default());
+    }
+    toml::from_str(&fs::read_to_string(path)?)
+        .map_err(|e| ErrorContactManager::BookInfo(e.to_string()))
+}
+
+/// replace the information of a book.
+pub fn set_book_info(
+    store: &Store,
+    book_name: &str,
+    info: &BookInfo,
+) -> Result<(), ErrorContactManager> {
+    check_write_book(store, book_name)?;
+    if let Some(name) = &info.sort_by {
+        if !PropertyName::to_strings()
+            .iter()
+            .any(|n| n.eq_ignore_ascii_case(name))
+        {
+            return Err(ErrorContactManager::BookInfo(format!(
+                "{name} is not a property to sort by"
+            )));
+        }
+    }
+    let _lock = lock_store(store)?;
+    let path = book_directory(book_name, store)?.join(BOOK_INFO_FILE);
+    let content =
+        toml::to_string(info).map_err(|e| ErrorContactManager::BookInfo(e.to_string()))?;
+    write_atomic(&path, &content)
+}
+
+impl BookInfo {
+    /// the CATEGORIES property to give to a new contact of the book, if the book has categories.
+    pub fn categories_property(&self) -> Result<Option<Property>, ErrorContactManager> {
+        if self.categories.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(Property::create_from_str(&format!(
+            "CATEGORIES:{}\n",
+            self.categories.join(",")
+        ))?))
+    }
+    /// sort the vcards by the value of the sort property, the vcards without it at the end.
+    pub fn sort(&self, vcards: &mut [Vcard]) {
+        if let Some(name) = &self.sort_by {
+            let name = name.to_uppercase();
+            vcards.sort_by_cached_key(|v| {
+                v.get_property_by_name(&name)
+                    .map(|p| p.get_value().to_string().to_lowercase())
+                    .map_or((1, String::new()), |value| (0, value))
+            });
+        }
+    }
+}
+
+/// size and activity of a book.
+#[derive(Clone, Debug)]
+pub struct BookSummary {
+    /// number of contacts in the book.
+    pub contacts: usize,
+    /// size in bytes of the contacts of the book.
+    pub size: u64,
+    /// time of the last modification of a contact of the book, None if the book is empty.
+    pub modified: Option<DateTime<Utc>>,
+}
+
+/// count the contacts of a book, their size and when they were last modified.
+pub fn book_summary(store: &Store, book_name: &str) -> Result<BookSummary, ErrorContactManager> {
+    check_read(store)?;
+    let mut summary = BookSummary {
+        contacts: 0,
+        size: 0,
+        modified: None,
+    };
+    for file in fs::read_dir(book_directory(book_name, store)?)? {
+        let path = file?.path();
+        if !path.extension().is_some_and(|e| e == "vcf") {
+            continue;
+        }
+        // the metadata of the contact, not of the link.
+        let metadata = match fs::metadata(&path) {
+            Ok(metadata) => metadata,
+            Err(e) => {
+                eprintln!(
+                    "maybe invalid link if contact was deleted manually ? {:?}, {:?}",
+                    path, e
+                );
+                continue;
+            }
+        };
+        let modified = DateTime::<Utc>::from(metadata.modified()?);
+        summary.contacts += 1;
+        summary.size += metadata.len();
+        summary.modified = summary.modified.max(Some(modified));
+    }
+    Ok(summary)
+}
@@ -0,0 +1,500 @@
+use std::{
+    fmt::Display,
+    fs::{read_to_string, remove_file, write, OpenOptions},
+    io::Write,
+    path::PathBuf,
+};
+
+use chrono::{DateTime, Utc};
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use vcard_parser::{
+    traits::{HasCardinality, HasName, HasValue},
+    vcard::property::Property,
+};
+
+use crate::{
+    access::{check_write_book, check_write_contacts, Store},
+    add_or_replace_property, add_to_book, create_contact_with_uuid, delete_properties,
+    error::ErrorContactManager,
+    find_books_where_contact_is_present,
+    paths::{path_vcard_file_from_uuid, place_data_file},
+    remove_from_book, replace_property,
+    store::{etag, lock_store},
+    vcard::vcards_by_uuid,
+};
+
+/// a modification of the contacts, kept in memory until it is applied.
+#[derive(Clone, Debug)]
+pub enum Change {
+    /// create a contact in a book, with the uid it will have.
+    CreateContact {
+        /// uid of the new contact.
+        uuid: Uuid,
+        /// full name of the new contact.
+        fullname: String,
+        /// name of the book.
+        book: String,
+    },
+    /// add a property to a contact.
+    AddProperty {
+        /// contact to modify.
+        uuid: Uuid,
+        /// property to add.
+        property: Property,
+    },
+    /// replace a property of a contact by another one.
+    ModifyProperty {
+        /// contact to modify.
+        uuid: Uuid,
+        /// property present in the contact.
+        old: Property,
+        /// property replacing the old one.
+        new: Property,
+    },
+    /// delete a property of a contact.
+    DeleteProperty {
+        /// contact to modify.
+        uuid: Uuid,
+        /// property to delete.
+        property: Property,
+    },
+    /// add a contact to a book.
+    AddToBook {
+        /// contact to add.
+        uuid: Uuid,
+        /// name of the book.
+        book: String,
+    },
+    /// remove a contact from a book, deleting it if it is not in any book anymore.
+    RemoveFromBook {
+        /// contact to remove.
+        uuid: Uuid,
+        /// name of the book.
+        book: String,
+    },
+}
+
+impl Change {
+    /// the contact concerned by the change.
+    pub fn uuid(&self) -> &Uuid {
+        match self {
+            Change::CreateContact { uuid, .. }
+            | Change::AddProperty { uuid, .. }
+            | Change::ModifyProperty { uuid, .. }
+            | Change::DeleteProperty { uuid, .. }
+            | Change::AddToBook { uuid, .. }
+            | Change::RemoveFromBook { uuid, .. } => uuid,
+        }
+    }
+    /// return an error if the access mode of the store or a read-only book refuses the change.
+    /// It can be checked before the change is kept, instead of failing when it is applied.
+    pub fn check_access(&self, store: &Store) -> Result<(), ErrorContactManager> {
+        match self {
+            Change::CreateContact { book, .. }
+            | Change::AddToBook { book, .. }
+            | Change::RemoveFromBook { book, .. } => check_write_book(store, book),
+            Change::AddProperty { uuid, .. }
+            | Change::ModifyProperty { uuid, .. }
+            | Change::DeleteProperty { uuid, .. } => check_write_contacts(store, &vec![*uuid]),
+        }
+    }
+    /// apply the change to the contacts stored.
+    /// Return an error if the change was made on a content modified since by another process:
+    /// the property to modify or delete is not in the contact anymore, the property added would replace another one,
+    /// or the contact does not exist anymore.
+    pub fn apply(&self, store: &Store) -> Result<(), ErrorContactManager> {
+        let _lock = lock_store(store)?;
+        if self.is_stale(store)? {
+            return Err(ErrorContactManager::Stale(*self.uuid()));
+        }
+        match self {
+            Change::CreateContact {
+                uuid,
+                fullname,
+                book,
+            } => create_contact_with_uuid(store, book, fullname, uuid)?,
+            Change::AddProperty { uuid, property } => {
+                add_or_replace_property(store, &vec![property], &vec![*uuid])?;
+            }
+            Change::ModifyProperty { uuid, old, new } => replace_property(store, uuid, old, new)?,
+            Change::DeleteProperty { uuid, property } => {
+                delete_properties(store, &vec![property], &vec![*uuid])?
+            }
+            Change::AddToBook { uuid, book } => {
+                if !find_books_where_contact_is_present(uuid, store)?.contains(book) {
+                    add_to_book(store, book, &vec![*uuid])?
+                }
+            }
+            Change::RemoveFromBook { uuid, book } => remove_from_book(store, book, &vec![*uuid])?,
+        }
+        self.journal(store)
+    }
+    /// true if the stored contact is not the one the change was made on anymore.
+    fn is_stale(&self, store: &Store) -> Result<bool, ErrorContactManager> {
+        if let Change::CreateContact { .. } | Change::RemoveFromBook { .. } = self {
+            return Ok(false);
+        }
+        let vcards = vcards_by_uuid(&vec![*self.uuid()], store)?;
+        let stored = vcards.first();
+        Ok(match self {
+            Change::CreateContact { .. } | Change::RemoveFromBook { .. } => false,
+            Change::ModifyProperty { old, .. } | Change::DeleteProperty { property: old, .. } => {
+                !stored.is_some_and(|v| v.get_properties().contains(old))
+            }
+            // a property which can be present once replaces the stored one, which was not there when the change was made.
+            Change::AddProperty { property, .. } => !stored.is_some_and(|v| {
+                property.cardinality() == "MULTIPLE"
+                    || v.get_properties()
+                        .iter()
+                        .all(|p| p.name() != property.name() || p == property)
+            }),
+            Change::AddToBook { .. } => stored.is_none(),
+        })
+    }
+    /// append the change applied to the journal, with the time.
+    fn journal(&self, app_name: &str) -> Result<(), ErrorContactManager> {
+        let entry = JournalEntry {
+            time: Utc::now().to_rfc3339(),
+            change: SavedChange::from(self),
+        };
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(journal_path(app_name)?)?;
+        writeln!(file, "{}", serde_json::to_string(&entry)?)?;
+        Ok(())
+    }
+}
+
+/// render the change as lines of a diff, without the contact.
+impl Display for Change {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Change::CreateContact { fullname, book, .. } => {
+                write!(f, "+ new contact {fullname} in book {book}")
+            }
+            Change::AddProperty { property, .. } => write!(f, "+ {}", raw(property)),
+            Change::ModifyProperty { old, new, .. } => {
+                write!(f, "- {}\n+ {}", raw(old), raw(new))
+            }
+            Change::DeleteProperty { property, .. } => write!(f, "- {}", raw(property)),
+            Change::AddToBook { book, .. } => write!(f, "+ in book {book}"),
+            Change::RemoveFromBook { book, .. } => write!(f, "- in book {book}"),
+        }
+    }
+}
+
+fn raw(property: &Property) -> String {
+    property.export().trim_end().to_string()
+}
+
+/// modifications waiting to be applied or discarded, in the order they were made.
+#[derive(Clone, Debug, Default)]
+pub struct ChangeSet {
+    changes: Vec<Change>,
+}
+
+impl ChangeSet {
+    /// an empty set of changes.
+    pub const fn new() -> Self {
+        ChangeSet {
+            changes: Vec::new(),
+        }
+    }
+    /// keep a new change.
+    pub fn push(&mut self, change: Change) {
+        self.changes.push(change)
+    }
+    /// the changes in the order they were made.
+    pub fn changes(&self) -> &Vec<Change> {
+        &self.changes
+    }
+    /// number of changes.
+    pub fn len(&self) -> usize {
+        self.changes.len()
+    }
+    /// true if there is no change.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+    /// discard the last change.
+    pub fn pop(&mut self) -> Option<Change> {
+        self.changes.pop()
+    }
+    /// discard the change at the index.
+    pub fn discard(&mut self, index: usize) -> Option<Change> {
+        (index < self.changes.len()).then(|| self.changes.remove(index))
+    }
+    /// discard the changes made after the first len ones.
+    pub fn truncate(&mut self, len: usize) {
+        self.changes.truncate(len)
+    }
+    /// discard every change.
+    pub fn clear(&mut self) {
+        self.changes.clear()
+    }
+    /// discard every change of a contact.
+    pub fn discard_contact(&mut self, uuid: &Uuid) {
+        self.changes.retain(|c| c.uuid() != uuid)
+    }
+    /// apply every change in order. A change applied is removed from the set, so on error the set contains the changes not yet applied.
+    pub fn apply(&mut self, store: &Store) -> Result<(), ErrorContactManager> {
+        while let Some(change) = self.changes.first() {
+            change.apply(store)?;
+            self.changes.remove(0);
+        }
+        Ok(())
+    }
+    /// apply the changes at the indexes and remove them from the set, the others stay.
+    pub fn apply_selected(
+        &mut self,
+        store: &Store,
+        indexes: &Vec<usize>,
+    ) -> Result<(), ErrorContactManager> {
+        let mut indexes = indexes.to_owned();
+        indexes.retain(|i| *i < self.changes.len());
+        indexes.sort();
+        indexes.dedup();
+        for (nb, index) in indexes.iter().enumerate() {
+            // every change applied before was removed, shifting the next ones.
+            let index = index - nb;
+            self.changes[index].apply(store)?;
+            self.changes.remove(index);
+        }
+        Ok(())
+    }
+    /// render the changes grouped by contact, with the full name of the contact.
+    pub fn diff(&self, app_name: &str) -> Result<String, ErrorContactManager> {
+        let mut uuids: Vec<Uuid> = Vec::new();
+        for change in self.changes.iter() {
+            if !uuids.contains(change.uuid()) {
+                uuids.push(*change.uuid());
+            }
+        }
+        let mut diff = String::new();
+        for uuid in uuids {
+            let fullname = match vcards_by_uuid(&vec![uuid], app_name)?
+                .first()
+                .and_then(|v| v.get_property_by_name("FN"))
+            {
+                Some(p) => p.get_value().to_string(),
+                // a contact created by the changes is not stored yet.
+                None => self
+                    .changes
+                    .iter()
+                    .find_map(|c| match c {
+                        Change::CreateContact {
+                            uuid: u, fullname, ..
+                        } if u == &uuid => Some(fullname.to_owned()),
+                        _ => None,
+                    })
+                    .unwrap_or_default(),
+            };
+            diff.push_str(&format!("{fullname} ({uuid}):\n"));
+            for change in self.changes.iter().filter(|c| c.uuid() == &uuid) {
+                diff.push_str(&format!("{change}\n"));
+            }
+        }
+        Ok(diff)
+    }
+}
+
+/// changes saved to be resumed later, with the tags of the contacts at the time of the save, see [etag].
+#[derive(Serialize, Deserialize)]
+struct SessionFile {
+    changes: Vec<SavedChange>,
+    contacts: Vec<(Uuid, String)>,
+}
+
+/// a change with the properties in their raw form.
+#[derive(Serialize, Deserialize)]
+enum SavedChange {
+    CreateContact {
+        uuid: Uuid,
+        fullname: String,
+        book: String,
+    },
+    AddProperty {
+        uuid: Uuid,
+        property: String,
+    },
+    ModifyProperty {
+        uuid: Uuid,
+        old: String,
+        new: String,
+    },
+    DeleteProperty {
+        uuid: Uuid,
+        property: String,
+    },
+    AddToBook {
+        uuid: Uuid,
+        book: String,
+    },
+    RemoveFromBook {
+        uuid: Uuid,
+        book: String,
+    },
+}
+
+impl From<&Change> for SavedChange {
+    fn from(change: &Change) -> Self {
+        match change.clone() {
+            Change::CreateContact {
+                uuid,
+                fullname,
+                book,
+            } => SavedChange::CreateContact {
+                uuid,
+                fullname,
+                book,
+            },
+            Change::AddProperty { uuid, property } => SavedChange::AddProperty {
+                uuid,
+                property: property.export(),
+            },
+            Change::ModifyProperty { uuid, old, new } => SavedChange::ModifyProperty {
+                uuid,
+                old: old.export(),
+                new: new.export(),
+            },
+            Change::DeleteProperty { uuid, property } => SavedChange::DeleteProperty {
+                uuid,
+                property: property.export(),
+            },
+            Change::AddToBook { uuid, book } => SavedChange::AddToBook { uuid, book },
+            Change::RemoveFromBook { uuid, book } => SavedChange::RemoveFromBook { uuid, book },
+        }
+    }
+}
+
+impl TryFrom<SavedChange> for Change {
+    type Error = ErrorContactManager;
+    fn try_from(saved: SavedChange) -> Result<Self, Self::Error> {
+        Ok(match saved {
+            SavedChange::CreateContact {
+                uuid,
+                fullname,
+                book,
+            } => Change::CreateContact {
+                uuid,
+                fullname,
+                book,
+            },
+            SavedChange::AddProperty { uuid, property } => Change::AddProperty {
+                uuid,
+                property: Property::create_from_str(&property)?,
+            },
+            SavedChange::ModifyProperty { uuid, old, new } => Change::ModifyProperty {
+                uuid,
+                old: Property::create_from_str(&old)?,
+                new: Property::create_from_str(&new)?,
+            },
+            SavedChange::DeleteProperty { uuid, property } => Change::DeleteProperty {
+                uuid,
+                property: Property::create_from_str(&property)?,
+            },
+            SavedChange::AddToBook { uuid, book } => Change::AddToBook { uuid, book },
+            SavedChange::RemoveFromBook { uuid, book } => Change::RemoveFromBook { uuid, book },
+        })
+    }
+}
+
+/// a change applied, as written in the journal.
+#[derive(Serialize, Deserialize)]
+struct JournalEntry {
+    time: String,
+    change: SavedChange,
+}
+
+/// path of the journal, where every change applied is written.
+pub fn journal_path(app_name: &str) -> Result<PathBuf, ErrorContactManager> {
+    place_data_file(app_name, "journal.jsonl")
+}
+
+/// the changes applied, the oldest first, with the time they were applied.
+pub fn journal(app_name: &str) -> Result<Vec<(DateTime<Utc>, Change)>, ErrorContactManager> {
+    let path = journal_path(app_name)?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let mut entries = Vec::new();
+    for line in read_to_string(path)?.lines().filter(|l| !l.is_empty()) {
+        let entry: JournalEntry = serde_json::from_str(line)?;
+        let time = DateTime::parse_from_rfc3339(&entry.time)
+            .map_err(|e| ErrorContactManager::Journal(e.to_string()))?
+            .to_utc();
+        entries.push((time, Change::try_from(entry.change)?));
+    }
+    Ok(entries)
+}
+
+/// changes of a previous session, not applied.
+pub struct Session {
+    /// the changes saved.
+    pub changes: ChangeSet,
+    /// contacts modified or deleted since the session was saved. Their changes may not make sense anymore.
+    pub conflicts: Vec<Uuid>,
+}
+
+/// path of the file of the saved session.
+pub fn session_path(app_name: &str) -> Result<PathBuf, ErrorContactManager> {
+    place_data_file(app_name, "session.json")
+}
+
+/// tag of the content of a contact, empty if the contact does not exist.
+fn tag_contact(uuid: &Uuid, app_name: &str) -> Result<String, ErrorContactManager> {
+    if !path_vcard_file_from_uuid(uuid, None, app_name)?.exists() {
+        return Ok(String::new());
+    }
+    etag(app_name, uuid)
+}
+
+impl ChangeSet {
+    /// save the changes to resume them later, replacing the previous saved session.
+    pub fn save_session(&self, app_name: &str) -> Result<(), ErrorContactManager> {
+        let mut contacts: Vec<(Uuid, String)> = Vec::new();
+        for change in self.changes.iter() {
+            if !contacts.iter().any(|(uuid, _)| uuid == change.uuid()) {
+                contacts.push((*change.uuid(), tag_contact(change.uuid(), app_name)?));
+            }
+        }
+        let session = SessionFile {
+            changes: self.changes.iter().map(SavedChange::from).collect(),
+            contacts,
+        };
+        write(session_path(app_name)?, serde_json::to_string(&session)?)?;
+        Ok(())
+    }
+}
+
+/// read the saved session, if there is one.
+pub fn load_session(app_name: &str) -> Result<Option<Session>, ErrorContactManager> {
+    let path = session_path(app_name)?;
+    if !path.exists() {
+        return Ok(None);
+    }
+    let session: SessionFile = serde_json::from_str(&read_to_string(path)?)?;
+    let mut changes = ChangeSet::new();
+    for saved in session.changes {
+        changes.push(saved.try_into()?);
+    }
+    let mut conflicts = Vec::new();
+    for (uuid, tag) in session.contacts {
+        if tag_contact(&uuid, app_name)? != tag {
+            conflicts.push(uuid);
+        }
+    }
+    Ok(Some(Session { changes, conflicts }))
+}
+
+/// delete the saved session, if there is one.
+pub fn discard_session(app_name: &str) -> Result<(), ErrorContactManager> {
+    let path = session_path(app_name)?;
+    if path.exists() {
+        remove_file(path)?;
+    }
+    Ok(())
+}
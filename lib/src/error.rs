@@ -1,4 +1,5 @@
 use thiserror::Error;
+use uuid::Uuid;
 use vcard_parser::{error::VcardError, vcard::Vcard};
 use xdg::BaseDirectoriesError;
 /// Errors from the API
@@ -28,6 +29,45 @@ pub enum ErrorContactManager {
     ImportError,
     #[error("The UID is not present, {0}")]
     UuidInexistant(Vcard),
+    #[error("the saved session can not be read or written")]
+    /// The file of the saved session is invalid.
+    Session(#[from] serde_json::Error),
+    #[error("the contacts are opened in read-only mode")]
+    /// A modification was attempted in read-only mode.
+    ReadOnly,
+    #[error("the book {0} is read-only")]
+    /// A modification was attempted on a read-only book or on a contact present in it.
+    ReadOnlyBook(String),
+    #[error("the contacts are opened in write-only mode")]
+    /// A reading was attempted in write-only mode.
+    WriteOnly,
+    #[error("the contact {0} was modified by another process since it was read, read it again before modifying it")]
+    /// A modification was based on an old content of the contact.
+    Stale(Uuid),
+    #[error("the information of the book is invalid: {0}")]
+    /// The file of the information of a book can not be read or written.
+    BookInfo(String),
+    #[error("\"{0}\" is not a valid tag, a tag can't be empty or contain a comma")]
+    /// A tag can't be written in CATEGORIES.
+    InvalidTag(String),
+    #[error("the smart book is invalid: {0}")]
+    /// The query of a smart book can not be read, written or evaluated.
+    SmartBook(String),
+    #[error("the template is invalid: {0}")]
+    /// The template of a property can not be read.
+    Template(String),
+    #[error("the property {0} is not an address")]
+    /// An address was read from a property other than ADR.
+    Address(String),
+    #[error("the journal can not be read: {0}")]
+    /// An entry of the journal is invalid.
+    Journal(String),
+    #[error("the data directory is already set, it can not be changed to {0}")]
+    /// The data directory was set twice.
+    DataDirectory(std::path::PathBuf),
+    #[error("invalid regular expression")]
+    /// The pattern to replace is not a valid regular expression.
+    Regex(#[from] regex::Error),
 }
 
 impl From<VcardError> for ErrorContactManager {
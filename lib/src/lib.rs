@@ -1,33 +1,64 @@
 #![warn(missing_docs)]
 #![doc = include_str!("../../README.md")]
 
+/// addresses of the ADR property, with their components.
+pub mod address;
+/// access mode of the contacts, to forbid modifications or readings.
+pub mod access;
 /// some tools to make life easier after calling the api functions.
 pub mod api_tools;
+/// information about the books.
+pub mod book;
+/// modifications kept in memory, to review them before applying or discarding them.
+pub mod changes;
 /// Right now, you can't give the api another path to search in another directory. The library use the XDG recommendations and "cm" for the app name.
 mod error;
 /// module to manage paths.
 pub mod paths;
+/// books defined by a saved query instead of links, read-only.
+pub mod smart;
+/// safe writes of the contacts, when several processes modify them.
+pub mod store;
+/// tags of the contacts, in the CATEGORIES property.
+pub mod tags;
+/// properties whose value is computed from the other properties of each contact.
+pub mod template;
+/// synchronization of contacts modified by several sources, with PID and CLIENTPIDMAP.
+pub mod sync;
 /// reimplement PropertyType with ValueEnum.
 pub mod vcard;
-use api_tools::generate_uid_property;
+use access::{check_read, check_write, check_write_book, check_write_contacts, Store};
+use address::{join_values, split_unescaped, split_values};
+use api_tools::uid_property;
+use book::book_info;
+use changes::{Change, ChangeSet};
 use error::ErrorContactManager;
-use paths::{book_directory, books_names, path_vcard_file_and_uid, path_vcard_file_from_uuid};
+use paths::{
+    book_directory, books_names, path_vcard_file_and_uid, path_vcard_file_from_uuid,
+    source_directory,
+};
+use store::{lock_store, write_atomic, write_vcard};
 /// Name of the app for XDG directories.
 use std::{
     fs::{self, remove_file},
     os::unix::fs::symlink,
     path::Path,
 };
+pub use chrono;
+use chrono::{DateTime, Utc};
+pub use regex;
+use regex::Regex;
 pub use uuid;
 use uuid::Uuid;
 use vcard::{
-    filter_vcards_by_properties, properties_show_from_vcards, read_contacts, uuids_from_vcards,
-    vcard_uuid, vcards_by_uuid, LogicalOperator,
+    filter_vcards_by_properties, properties_show_from_vcards, read_contacts, rev,
+    uuids_from_vcards, vcard_uuid, vcards_by_uuid, LogicalOperator,
 };
 pub use vcard_parser;
 use vcard_parser::{
+    constants::PropertyName,
     parse_vcards,
-    traits::HasValue,
+    traits::{HasName, HasParameters, HasValue},
     vcard::{
         property::{property_fn::PropertyFnData, property_uid::PropertyUidData, Property},
         value::{value_text::ValueTextData, Value},
@@ -37,41 +68,94 @@ use vcard_parser::{
 
 use crate::paths::books_directory;
 /// get the vcards from filters properties with operator logic and from book or all.
+/// If recursive, the books nested in the book are searched too.
 pub fn find_uids(
-    app_name: &str,
+    store: &Store,
     book_name: Option<&str>,
+    recursive: bool,
     filter_properties: &Vec<Property>,
     lo: &LogicalOperator,
     forgive: bool,
 ) -> Result<Vec<Uuid>, ErrorContactManager> {
-    let vcards_all = read_contacts(book_name, app_name)?;
+    check_read(store)?;
+    let vcards_all = read_contacts(book_name, recursive, store)?;
     let vcards = filter_vcards_by_properties(&vcards_all, filter_properties, forgive, lo)?;
     uuids_from_vcards(&vcards.iter().collect())
 }
 
+/// get the vcards having an EMAIL property equal to the address, ignoring case, from book or all.
+pub fn find_uids_by_email(
+    store: &Store,
+    book_name: Option<&str>,
+    email: &str,
+) -> Result<Vec<Uuid>, ErrorContactManager> {
+    check_read(store)?;
+    let vcards = read_contacts(book_name, false, store)?;
+    let email = email.trim().to_lowercase();
+    let vcards = vcards
+        .iter()
+        .filter(|v| {
+            v.get_properties().iter().any(|p| {
+                p.name() == PropertyName::EMAIL
+                    && p.get_value().to_string().trim().to_lowercase() == email
+            })
+        })
+        .collect();
+    uuids_from_vcards(&vcards)
+}
+
+/// get the contacts modified after the time, from book or all.
+/// Contacts without REV are returned, as the time of their last modification is unknown.
+/// If recursive, the books nested in the book are searched too.
+pub fn modified_since(
+    store: &Store,
+    book_name: Option<&str>,
+    recursive: bool,
+    since: &DateTime<Utc>,
+) -> Result<Vec<Uuid>, ErrorContactManager> {
+    check_read(store)?;
+    let vcards = read_contacts(book_name, recursive, store)?;
+    let vcards = vcards
+        .iter()
+        .filter(|v| rev(v).is_none_or(|rev| &rev > since))
+        .collect();
+    uuids_from_vcards(&vcards)
+}
+
 /// create a new address book with a name. The book will be empty.
 /// Return an error if it already exists.
-pub fn create_book(book_name: &str, app_name: &str) -> Result<(), ErrorContactManager> {
-    let path_book = book_directory(book_name, app_name)?;
+pub fn create_book(book_name: &str, store: &Store) -> Result<(), ErrorContactManager> {
+    check_write(store)?;
+    let _lock = lock_store(store)?;
+    let path_book = book_directory(book_name, store)?;
     fs::create_dir_all(&path_book)?;
     Ok(())
 }
 /// delete an adressbook. Return an error it doesn't exists.
 /// All links in the book will be removed, but no contacts will de deleted from the folder contacts.
-pub fn delete_book(book_name: &str, app_name: &str) -> Result<(), ErrorContactManager> {
-    let path_book = book_directory(book_name, app_name)?;
+/// The books nested in the book are deleted too.
+pub fn delete_book(book_name: &str, store: &Store) -> Result<(), ErrorContactManager> {
+    check_write_book(store, book_name)?;
+    let _lock = lock_store(store)?;
+    let path_book = book_directory(book_name, store)?;
     fs::remove_dir_all(&path_book)?;
     Ok(())
 }
 /// rename a book. All contacts in the new book will be preserved.
+/// The books nested in the book are moved with it, and the new name can be nested in another book, example: "work/clients".
 pub fn rename_book(
     book_name: &str,
     book_new_name: &str,
-    app_name: &str,
+    store: &Store,
 ) -> Result<(), ErrorContactManager> {
-    let path_book = book_directory(book_name, app_name)?;
-    let mut path_new = books_directory(app_name)?;
+    check_write_book(store, book_name)?;
+    let _lock = lock_store(store)?;
+    let path_book = book_directory(book_name, store)?;
+    let mut path_new = books_directory(store)?;
     path_new.push(book_new_name);
+    if let Some(parent) = path_new.parent() {
+        fs::create_dir_all(parent)?;
+    }
     fs::rename(path_book, path_new)?;
     Ok(())
 }
@@ -79,44 +163,70 @@ pub fn rename_book(
 /// You can't have two contacts with the same fullname.
 /// Will give the uuid if the contact was successfully created.
 pub fn create_contact(
-    app_name: &str,
+    store: &Store,
     book_name: &str,
     values_fn: &Vec<String>,
 ) -> Result<Vec<Uuid>, ErrorContactManager> {
-    // load every contacts from book
-    let vcards = read_contacts(None, app_name)?;
-    // find the vcard by comparing FullName value.
     let mut uuids = Vec::new();
     for value_fn in values_fn {
-        let mut fn_property = Property::PropertyFn(PropertyFnData::default());
-        fn_property.set_value(Value::ValueText(ValueTextData {
-            value: value_fn.to_owned(),
-        }))?;
-
-        if filter_vcards_by_properties(&vcards, &vec![fn_property], false, &LogicalOperator::Or)?
-            .is_empty()
-        {
-            let (p_uuid, uuid) = generate_uid_property()?;
-            let mut vcard = Vcard::new(&value_fn);
-            vcard.set_property(&p_uuid)?;
-            let data = vcard.to_string();
-            fs::write(path_vcard_file_and_uid(&vcard, None, app_name)?.0, data)?;
-            uuids.push(uuid);
-            add_to_book(app_name, book_name, &vec![uuid])?;
-        } else {
-            return Err(ErrorContactManager::AlreadyExist);
-        }
+        let uuid = Uuid::new_v4();
+        create_contact_with_uuid(store, book_name, value_fn, &uuid)?;
+        uuids.push(uuid);
     }
     Ok(uuids)
 }
 
-fn find_books_where_contact_is_present(
+/// create a new contact with a fullname and the uid given, so the contact can be known before being created.
+/// Will fail if a contact has the same fullname or the same uid.
+pub fn create_contact_with_uuid(
+    store: &Store,
+    book_name: &str,
+    value_fn: &str,
+    uuid: &Uuid,
+) -> Result<(), ErrorContactManager> {
+    check_write_book(store, book_name)?;
+    let _lock = lock_store(store)?;
+    // load every contacts from book
+    let vcards = read_contacts(None, false, store)?;
+    // find the vcard by comparing FullName value.
+    let mut fn_property = Property::PropertyFn(PropertyFnData::default());
+    fn_property.set_value(Value::ValueText(ValueTextData {
+        value: value_fn.to_owned(),
+    }))?;
+    if !filter_vcards_by_properties(&vcards, &vec![fn_property], false, &LogicalOperator::Or)?
+        .is_empty()
+        || path_vcard_file_from_uuid(uuid, None, store)?.exists()
+    {
+        return Err(ErrorContactManager::AlreadyExist);
+    }
+    let mut vcard = new_vcard(store, book_name, value_fn, uuid)?;
+    write_vcard(&path_vcard_file_and_uid(&vcard, None, store)?.0, &mut vcard)?;
+    add_to_book(store, book_name, &vec![*uuid])
+}
+
+/// the vcard of a new contact of a book, with the properties given to the new contacts of the book.
+pub fn new_vcard(
+    app_name: &str,
+    book_name: &str,
+    value_fn: &str,
+    uuid: &Uuid,
+) -> Result<Vcard, ErrorContactManager> {
+    let mut vcard = Vcard::new(value_fn);
+    vcard.set_property(&uid_property(uuid)?)?;
+    if let Some(categories) = book_info(app_name, book_name)?.categories_property()? {
+        vcard.set_property(&categories)?;
+    }
+    Ok(vcard)
+}
+
+/// find the names of the books where the contact is present.
+pub fn find_books_where_contact_is_present(
     uuid: &Uuid,
     app_name: &str,
 ) -> Result<Vec<String>, ErrorContactManager> {
     let mut books_find = Vec::new();
     for b in books_names(app_name)? {
-        let vcards = read_contacts(Some(&b), app_name)?;
+        let vcards = read_contacts(Some(&b), false, app_name)?;
 
         let mut property_uuid = Property::PropertyUid(PropertyUidData::default());
         property_uuid.set_value(Value::ValueText(ValueTextData {
@@ -131,11 +241,13 @@ fn find_books_where_contact_is_present(
     Ok(books_find)
 }
 /// delete a contact, removing it also from any book he was.
-pub fn delete_contacts(uuids: &Vec<Uuid>, app_name: &str) -> Result<(), ErrorContactManager> {
+pub fn delete_contacts(uuids: &Vec<Uuid>, store: &Store) -> Result<(), ErrorContactManager> {
+    check_write_contacts(store, uuids)?;
+    let _lock = lock_store(store)?;
     for uuid in uuids {
-        fs::remove_file(path_vcard_file_from_uuid(&uuid, None, app_name)?)?;
-        for book_name in books_names(app_name)? {
-            let file = path_vcard_file_from_uuid(&uuid, Some(&book_name), app_name)?;
+        fs::remove_file(path_vcard_file_from_uuid(&uuid, None, store)?)?;
+        for book_name in books_names(store)? {
+            let file = path_vcard_file_from_uuid(&uuid, Some(&book_name), store)?;
             if file.exists() {
                 remove_file(file)?
             }
@@ -147,19 +259,21 @@ pub fn delete_contacts(uuids: &Vec<Uuid>, app_name: &str) -> Result<(), ErrorCon
 /// remove a contact from a book
 /// will remove the contact for the contacts folder if it doesn't exist in books anymore.
 pub fn remove_from_book(
-    app_name: &str,
+    store: &Store,
     book_name: &str,
     uuids: &Vec<Uuid>,
 ) -> Result<(), ErrorContactManager> {
+    check_write_book(store, book_name)?;
+    let _lock = lock_store(store)?;
     for uuid in uuids {
-        let file = path_vcard_file_from_uuid(&uuid, Some(book_name), app_name)?;
+        let file = path_vcard_file_from_uuid(&uuid, Some(book_name), store)?;
         if file.exists() {
             fs::remove_file(file)?;
         }
         // does contact still exist in other books ?
         for uuid in uuids {
-            if find_books_where_contact_is_present(uuid, app_name)?.is_empty() {
-                delete_contacts(&vec![*uuid], app_name)?;
+            if find_books_where_contact_is_present(uuid, store)?.is_empty() {
+                delete_contacts(&vec![*uuid], store)?;
             }
         }
     }
@@ -167,15 +281,17 @@ pub fn remove_from_book(
 }
 /// add a contact to a book
 pub fn add_to_book(
-    app_name: &str,
+    store: &Store,
     book_name: &str,
     uuids: &Vec<Uuid>,
 ) -> Result<(), ErrorContactManager> {
+    check_write_book(store, book_name)?;
+    let _lock = lock_store(store)?;
     for uuid in uuids {
-        let file_path = path_vcard_file_from_uuid(uuid, None, app_name)?;
+        let file_path = path_vcard_file_from_uuid(uuid, None, store)?;
         if file_path.exists() {
             let file = format!("{}.vcf", uuid.to_string());
-            let mut file_book = book_directory(book_name, app_name)?;
+            let mut file_book = book_directory(book_name, store)?;
             file_book.push(file);
             symlink(file_path, file_book)?;
         } else {
@@ -185,14 +301,63 @@ pub fn add_to_book(
     return Ok(());
 }
 
+/// add the contacts of a book to another book, keeping them in the first one.
+/// Contacts already in the destination book are ignored. Return an error if a contact is not in the origin book.
+pub fn copy_contacts(
+    store: &Store,
+    from: &str,
+    to: &str,
+    uuids: &Vec<Uuid>,
+) -> Result<(), ErrorContactManager> {
+    check_write_book(store, to)?;
+    let _lock = lock_store(store)?;
+    let origin = uuids_from_vcards(
+        &read_contacts(Some(from), false, store)?
+            .iter()
+            .collect(),
+    )?;
+    if uuids.iter().any(|uuid| !origin.contains(uuid)) {
+        return Err(ErrorContactManager::Inexistant);
+    }
+    let present = uuids_from_vcards(
+        &read_contacts(Some(to), false, store)?
+            .iter()
+            .collect(),
+    )?;
+    let uuids = uuids
+        .iter()
+        .filter(|uuid| !present.contains(uuid))
+        .copied()
+        .collect();
+    add_to_book(store, to, &uuids)
+}
+
+/// move the contacts of a book to another book.
+/// Contacts already in the destination book are only removed from the origin book. Return an error if a contact is not in the origin book.
+pub fn move_contacts(
+    store: &Store,
+    from: &str,
+    to: &str,
+    uuids: &Vec<Uuid>,
+) -> Result<(), ErrorContactManager> {
+    if from == to {
+        return Ok(());
+    }
+    check_write_book(store, from)?;
+    let _lock = lock_store(store)?;
+    copy_contacts(store, from, to, uuids)?;
+    remove_from_book(store, from, uuids)
+}
+
 /// find some properties of vcards, filterable by book.
 pub fn find_properties(
-    app_name: &str,
+    store: &Store,
     properties_show: &Vec<Property>,
     uuids: &Vec<Uuid>,
     forgive: bool,
 ) -> Result<Vec<(Uuid, Vec<Property>)>, ErrorContactManager> {
-    let mut vcards = vcards_by_uuid(uuids, app_name)?;
+    check_read(store)?;
+    let mut vcards = vcards_by_uuid(uuids, store)?;
     Ok(properties_show_from_vcards(
         &mut vcards,
         properties_show,
@@ -200,12 +365,19 @@ pub fn find_properties(
     )?)
 }
 
-/// get all vcards from book
+/// get all vcards from book, sorted as the book prefers.
+/// If recursive, the contacts of the books nested in the book are given too, once.
 pub fn vcards_from_book(
-    app_name: &str,
+    store: &Store,
     book_name: Option<&str>,
+    recursive: bool,
 ) -> Result<Vec<Vcard>, ErrorContactManager> {
-    Ok(read_contacts(book_name, app_name)?)
+    check_read(store)?;
+    let mut vcards = read_contacts(book_name, recursive, store)?;
+    if let Some(book_name) = book_name {
+        book_info(store, book_name)?.sort(&mut vcards);
+    }
+    Ok(vcards)
 }
 
 /// add or replace if matches a property to first contact equal with anoter property value, filterable by book.
@@ -213,11 +385,13 @@ pub fn vcards_from_book(
 /// if the PID match, it will replace the property.
 /// This function will return the set property including the pid number to allow replacing it.
 pub fn add_or_replace_property(
-    app_name: &str,
+    store: &Store,
     properties_add: &Vec<&Property>,
     uuids: &Vec<Uuid>,
 ) -> Result<Vec<(Uuid, Vec<Property>)>, ErrorContactManager> {
-    let mut vcards = vcards_by_uuid(uuids, app_name)?;
+    check_write_contacts(store, uuids)?;
+    let _lock = lock_store(store)?;
+    let mut vcards = vcards_by_uuid(uuids, store)?;
     let mut properties_id = vec![];
     for vcard in &mut vcards {
         let mut properties = vec![];
@@ -226,39 +400,127 @@ pub fn add_or_replace_property(
         }
         let uuid = vcard_uuid(&vcard)?;
         properties_id.push((uuid, properties));
-        fs::write(
-            path_vcard_file_from_uuid(&uuid, None, app_name)?,
-            vcard.to_string(),
-        )?;
+        write_vcard(&path_vcard_file_from_uuid(&uuid, None, store)?, vcard)?;
     }
     Ok(properties_id)
 }
+/// replace a property of a contact by another one, in one write.
+pub fn replace_property(
+    store: &Store,
+    uuid: &Uuid,
+    old: &Property,
+    new: &Property,
+) -> Result<(), ErrorContactManager> {
+    check_write_contacts(store, &vec![*uuid])?;
+    let _lock = lock_store(store)?;
+    let mut vcards = vcards_by_uuid(&vec![*uuid], store)?;
+    let vcard = vcards.first_mut().ok_or(ErrorContactManager::Inexistant)?;
+    vcard.remove_property(old)?;
+    vcard.set_property(new)?;
+    write_vcard(&path_vcard_file_from_uuid(uuid, None, store)?, vcard)?;
+    Ok(())
+}
+/// the pattern and the replacement to give to replace_in_values.
+/// If not regex, the text is matched literally and the replacement is written as is, "$1" included.
+pub fn replace_pattern(
+    from: &str,
+    to: &str,
+    regex: bool,
+) -> Result<(Regex, String), ErrorContactManager> {
+    if regex {
+        Ok((Regex::new(from)?, to.to_string()))
+    } else {
+        Ok((Regex::new(&regex::escape(from))?, to.replace('$', "$$")))
+    }
+}
+/// the changes replacing the matches of the pattern in the values of a property, to review them before applying.
+/// Nothing is written, the ChangeSet returned is applied with its apply method.
+/// The parameters of the properties are kept, properties without match have no change.
+/// The replacement can refer to the groups of the pattern, example: "$1".
+pub fn replace_in_values(
+    store: &Store,
+    uuids: &Vec<Uuid>,
+    property_name: &str,
+    pattern: &Regex,
+    replacement: &str,
+) -> Result<ChangeSet, ErrorContactManager> {
+    check_read(store)?;
+    replace_changes(
+        &vcards_by_uuid(uuids, store)?,
+        property_name,
+        pattern,
+        replacement,
+    )
+}
+/// the changes replacing the matches of the pattern in the values of a property of the vcards given, like replace_in_values.
+/// The pattern is matched on each value unescaped, the components and the values of a list being matched separately.
+pub fn replace_changes(
+    vcards: &[Vcard],
+    property_name: &str,
+    pattern: &Regex,
+    replacement: &str,
+) -> Result<ChangeSet, ErrorContactManager> {
+    let mut changes = ChangeSet::new();
+    for vcard in vcards {
+        let uuid = vcard_uuid(vcard)?;
+        for old in vcard
+            .get_properties()
+            .into_iter()
+            .filter(|p| p.name().eq_ignore_ascii_case(property_name))
+        {
+            // the value is written again only if a replacement modified it.
+            let mut matched = false;
+            let replaced = split_unescaped(&old.get_value().to_string(), ';')
+                .iter()
+                .map(|component| {
+                    let values: Vec<String> = split_values(component)
+                        .iter()
+                        .map(|value| {
+                            let new = pattern.replace_all(value, replacement).into_owned();
+                            matched |= &new != value;
+                            new
+                        })
+                        .collect();
+                    join_values(&values)
+                })
+                .collect::<Vec<String>>()
+                .join(";");
+            if !matched {
+                continue;
+            }
+            let mut new = Property::create_from_str(&format!("{}:{replaced}\n", old.name()))?;
+            new.set_parameters(old.get_parameters());
+            changes.push(Change::ModifyProperty { uuid, old, new });
+        }
+    }
+    Ok(changes)
+}
 /// delete properties for every contacts matched with uuids.
 pub fn delete_properties(
-    app_name: &str,
+    store: &Store,
     property_delete: &Vec<&Property>,
     uuids: &Vec<Uuid>,
 ) -> Result<(), ErrorContactManager> {
-    let mut vcards = vcards_by_uuid(&uuids, app_name)?;
+    check_write_contacts(store, uuids)?;
+    let _lock = lock_store(store)?;
+    let mut vcards = vcards_by_uuid(&uuids, store)?;
     for vcard in &mut vcards {
         for p in property_delete {
             vcard.remove_property(p)?;
         }
         let uuid = vcard_uuid(&vcard)?;
-        fs::write(
-            path_vcard_file_from_uuid(&uuid, None, app_name)?,
-            vcard.to_string(),
-        )?;
+        write_vcard(&path_vcard_file_from_uuid(&uuid, None, store)?, vcard)?;
     }
     Ok(())
 }
 /// render an index with the chosen properties. Will only render a contact line if every property exist.
 pub fn generate_index(
-    app_name: &str,
+    store: &Store,
     book_name: Option<&str>,
     properties: &Vec<Property>,
 ) -> Result<Vec<String>, ErrorContactManager> {
-    let vcards = read_contacts(book_name, app_name)?;
+    check_read(store)?;
+    let vcards = read_contacts(book_name, false, store)?;
     let uuids = properties_show_from_vcards(&vcards, &properties, false)?;
     let mut index = vec![];
 
@@ -275,8 +537,14 @@ pub fn generate_index(
 }
 
 /// export to a string all contacts of a book or of all books if book name not given
-pub fn export(book_name: Option<&str>, app_name: &str) -> Result<String, ErrorContactManager> {
-    let contacts = read_contacts(book_name, app_name)?;
+/// If recursive, the contacts of the books nested in the book are exported too.
+pub fn export(
+    book_name: Option<&str>,
+    recursive: bool,
+    store: &Store,
+) -> Result<String, ErrorContactManager> {
+    check_read(store)?;
+    let contacts = read_contacts(book_name, recursive, store)?;
     let mut all = String::new();
     for c in contacts {
         all.push_str(&c.to_string());
@@ -287,7 +555,111 @@ pub fn export(book_name: Option<&str>, app_name: &str) -> Result<String, ErrorCo
 /// import all vcards from a file into a book name.
 /// if a contact is invalid, the import will be canceled.
 /// If no valid uid is discovered for each contact, it will be created.
-pub fn import(path: &Path, book_name: &str, app_name: &str) -> Result<(), ErrorContactManager> {
+pub fn import(path: &Path, book_name: &str, store: &Store) -> Result<(), ErrorContactManager> {
+    check_write_book(store, book_name)?;
+    let _lock = lock_store(store)?;
+    let mut contacts = parse_vcards_file(path)?;
+    let mut uuids = Vec::new();
+    for c in &mut contacts {
+        let uuid = valid_uuid(c)?;
+        write_vcard(&path_vcard_file_from_uuid(&uuid, None, store)?, c)?;
+        uuids.push(uuid);
+    }
+    add_to_book(store, book_name, &uuids)
+}
+
+/// import all vcards from a file coming from a source, identified by an URI, into a book name.
+/// Contacts already present are merged with the imported version instead of being replaced, see [sync::merge].
+/// A contact without uid takes the uid of the contact imported before from the source with the same full name,
+/// a contact with an uid which is not an uuid always takes the same uuid, so importing again does not duplicate them.
+/// if a contact is invalid, the import will be canceled.
+pub fn import_from_source(
+    path: &Path,
+    book_name: &str,
+    store: &Store,
+    source: &str,
+) -> Result<(), ErrorContactManager> {
+    check_write_book(store, book_name)?;
+    let _lock = lock_store(store)?;
+    let previous = source_vcards(store, source)?;
+    for c in &mut parse_vcards_file(path)? {
+        let uuid = match c.get_property_by_name(PropertyName::UID) {
+            Some(uid) => {
+                let uid = uid.get_value().to_string();
+                Uuid::try_parse(&uid).unwrap_or_else(|_| {
+                    Uuid::new_v5(&Uuid::NAMESPACE_URL, format!("{source}#{uid}").as_bytes())
+                })
+            }
+            None => sync::known_uuid(c, &previous).unwrap_or_else(Uuid::new_v4),
+        };
+        c.set_property(&uid_property(&uuid)?)?;
+        merge_contact(store, book_name, c, source)?;
+    }
+    Ok(())
+}
+
+/// the versions of the contacts last given by a source.
+fn source_vcards(app_name: &str, source: &str) -> Result<Vec<Vcard>, ErrorContactManager> {
+    let mut vcards = Vec::new();
+    for entry in fs::read_dir(source_directory(app_name, source)?)? {
+        vcards.extend(parse_vcards(&fs::read_to_string(entry?.path())?)?);
+    }
+    Ok(vcards)
+}
+
+/// merge a version of a contact coming from a source, identified by an URI, into the stored contact with the same UID.
+/// The contact is created in the book if it is not present, unless it was deleted since the source gave the same version.
+/// PIDs of the source are given to the properties of the version, see [sync::assign_pids].
+/// The version is kept to know what the source modified at the next merge.
+pub fn merge_contact(
+    store: &Store,
+    book_name: &str,
+    vcard: &Vcard,
+    source: &str,
+) -> Result<Uuid, ErrorContactManager> {
+    let uuid = vcard_uuid(vcard)?;
+    check_write_book(store, book_name)?;
+    let _lock = lock_store(store)?;
+    let path = path_vcard_file_from_uuid(&uuid, None, store)?;
+    let path_previous = source_directory(store, source)?.join(format!("{uuid}.vcf"));
+    let previous = if path_previous.exists() {
+        parse_vcards(&fs::read_to_string(&path_previous)?)?
+            .into_iter()
+            .next()
+    } else {
+        None
+    };
+    let mut remote = vcard.clone();
+    sync::assign_pids(&mut remote, source, previous.as_ref())?;
+    let mut merged = if path.exists() {
+        check_write_contacts(store, &vec![uuid])?;
+        let local = vcards_by_uuid(&vec![uuid], store)?;
+        sync::merge(
+            local.first().ok_or(ErrorContactManager::Inexistant)?,
+            &remote,
+            previous.as_ref(),
+            source,
+        )?
+    } else if previous
+        .as_ref()
+        .is_some_and(|previous| sync::same_version(previous, &remote))
+    {
+        // deleted locally and not modified by the source since.
+        return Ok(uuid);
+    } else {
+        remote.clone()
+    };
+    write_vcard(&path, &mut merged)?;
+    // the previous version decides what was deleted at the next import, it must not be left truncated.
+    write_atomic(&path_previous, &remote.to_string())?;
+    if !find_books_where_contact_is_present(&uuid, store)?.contains(&book_name.to_string()) {
+        add_to_book(store, book_name, &vec![uuid])?;
+    }
+    Ok(uuid)
+}
+
+/// parse every vcard of a file.
+fn parse_vcards_file(path: &Path) -> Result<Vec<Vcard>, ErrorContactManager> {
     if path.is_dir() {
         return Err(ErrorContactManager::ImportError);
     }
@@ -298,25 +670,18 @@ pub fn import(path: &Path, book_name: &str, app_name: &str) -> Result<(), ErrorC
     } else {
         path.to_owned()
     };
-    // parse vcards
-    let mut contacts = parse_vcards(&fs::read_to_string(&path)?)?;
-    let mut uuids = Vec::new();
-    for mut c in &mut contacts {
-        // verify that uuid is present and valid
-        let uuid = match c.get_property_ref(&Property::PropertyUid(PropertyUidData::default())) {
-            Some(p) => match Uuid::try_parse(&p.get_value().to_string()) {
-                Ok(uuid) => uuid,
-                Err(_) => set_new_uuid(&mut c)?,
-            },
-            None => set_new_uuid(&mut c)?,
-        };
-        fs::write(
-            path_vcard_file_from_uuid(&uuid, None, app_name)?,
-            c.to_string(),
-        )?;
-        uuids.push(uuid);
+    Ok(parse_vcards(&fs::read_to_string(&path)?)?)
+}
+
+/// the uuid of the vcard, replacing it by a new one if it is absent or invalid.
+fn valid_uuid(vcard: &mut Vcard) -> Result<Uuid, ErrorContactManager> {
+    match vcard.get_property_ref(&Property::PropertyUid(PropertyUidData::default())) {
+        Some(p) => match Uuid::try_parse(&p.get_value().to_string()) {
+            Ok(uuid) => Ok(uuid),
+            Err(_) => set_new_uuid(vcard),
+        },
+        None => set_new_uuid(vcard),
     }
-    add_to_book(app_name, book_name, &uuids)
 }
 
 fn set_new_uuid(vcard: &mut Vcard) -> Result<Uuid, ErrorContactManager> {
@@ -1,34 +1,117 @@
-use std::{fs::read_dir, path::PathBuf};
+use std::{
+    fs::{create_dir_all, read_dir},
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
 
+use sha2::{Digest, Sha256};
 use uuid::Uuid;
 use vcard_parser::{traits::HasValue, vcard::Vcard};
 
 use crate::ErrorContactManager;
 
+static DATA_DIRECTORY: OnceLock<PathBuf> = OnceLock::new();
+
+/// use this directory instead of the data directory of XDG for every following call of the library.
+/// It can be set only once, before the contacts are read.
+pub fn set_data_directory(path: PathBuf) -> Result<(), ErrorContactManager> {
+    DATA_DIRECTORY
+        .set(path)
+        .map_err(ErrorContactManager::DataDirectory)
+}
+
+/// return the data directory of the application, where the books and contacts directories are.
+pub fn data_directory(app_name: &str) -> Result<PathBuf, ErrorContactManager> {
+    if let Some(path) = DATA_DIRECTORY.get() {
+        return Ok(path.to_owned());
+    }
+    let xdg_dirs = xdg::BaseDirectories::with_prefix(app_name)?;
+    Ok(xdg_dirs.get_data_home())
+}
+
+/// return the configuration directory of the application, it is not changed by set_data_directory.
+pub fn config_directory(app_name: &str) -> Result<PathBuf, ErrorContactManager> {
+    let xdg_dirs = xdg::BaseDirectories::with_prefix(app_name)?;
+    Ok(xdg_dirs.get_config_home())
+}
+
+/// return the path of a directory in the data directory, creating it if it does not exist.
+fn create_data_directory(app_name: &str, name: &str) -> Result<PathBuf, ErrorContactManager> {
+    let path = data_directory(app_name)?.join(name);
+    create_dir_all(&path)?;
+    Ok(path)
+}
+
+/// return the path of a file in the data directory, creating the data directory if it does not exist.
+pub(crate) fn place_data_file(app_name: &str, name: &str) -> Result<PathBuf, ErrorContactManager> {
+    let directory = data_directory(app_name)?;
+    create_dir_all(&directory)?;
+    Ok(directory.join(name))
+}
+
 /// return the default path of books directory, creating it if it does not exist.
 pub fn books_directory(app_name: &str) -> Result<PathBuf, ErrorContactManager> {
-    let xdg_dirs = xdg::BaseDirectories::with_prefix(app_name)?;
-    Ok(xdg_dirs.create_data_directory("books")?)
+    create_data_directory(app_name, "books")
 }
 
-/// will return the names of the available books.
+/// will return the names of the available books, sorted.
+/// Nested books are named with the names of their parents, separated by a slash, example: "work/clients".
 pub fn books_names(app_name: &str) -> Result<Vec<String>, ErrorContactManager> {
     let mut paths = Vec::new();
-    let dirs = read_dir(books_directory(app_name)?)?;
-    for dir in dirs {
-        paths.push(
-            dir?.file_name()
-                .into_string()
-                .expect("non utf-8 caracter on name of book"),
-        )
-    }
+    push_books_names(&books_directory(app_name)?, "", &mut paths)?;
+    paths.sort();
     Ok(paths)
 }
 
+fn push_books_names(
+    dir: &Path,
+    parent: &str,
+    paths: &mut Vec<String>,
+) -> Result<(), ErrorContactManager> {
+    for entry in read_dir(dir)? {
+        let entry = entry?;
+        // contacts are links to files, only books are directories.
+        if !entry.file_type()?.is_dir() {
+            continue;
+        }
+        let name = entry
+            .file_name()
+            .into_string()
+            .expect("non utf-8 caracter on name of book");
+        let name = if parent.is_empty() {
+            name
+        } else {
+            format!("{parent}/{name}")
+        };
+        push_books_names(&entry.path(), &name, paths)?;
+        paths.push(name);
+    }
+    Ok(())
+}
+
+/// will return the names of the books nested in a book, at any depth.
+pub fn sub_books_names(app_name: &str, book_name: &str) -> Result<Vec<String>, ErrorContactManager> {
+    let prefix = format!("{book_name}/");
+    let mut names = books_names(app_name)?;
+    names.retain(|name| name.starts_with(&prefix));
+    Ok(names)
+}
+
 /// return the default path of contacts directory, creating it if it does not exist.
 pub fn contacts_directory(app_name: &str) -> Result<PathBuf, ErrorContactManager> {
-    let xdg_dirs = xdg::BaseDirectories::with_prefix(app_name).unwrap();
-    Ok(xdg_dirs.create_data_directory("contacts")?)
+    create_data_directory(app_name, "contacts")
+}
+
+/// return the directory of the versions of the contacts last given by a source, creating it if it does not exist.
+pub(crate) fn source_directory(app_name: &str, source: &str) -> Result<PathBuf, ErrorContactManager> {
+    // the URI of the source can not be a name of directory, its hash is used instead.
+    let name = format!("{:x}", Sha256::digest(source.as_bytes()));
+    create_data_directory(app_name, &format!("sources/{name}"))
+}
+
+/// return the default path of smart books directory, creating it if it does not exist.
+pub fn smart_books_directory(app_name: &str) -> Result<PathBuf, ErrorContactManager> {
+    create_data_directory(app_name, "smart-books")
 }
 pub(crate) fn path_vcard_file_and_uid<'a>(
     vcard: &Vcard,
@@ -0,0 +1,135 @@
+use std::{fs, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+use vcard_parser::vcard::{property::Property, Vcard};
+
+use crate::{
+    access::{check_write, Store},
+    error::ErrorContactManager,
+    paths::smart_books_directory,
+    store::{lock_store, write_atomic},
+    tags::has_tag,
+    vcard::{filter_vcards_by_properties, read_contacts, LogicalOperator},
+};
+
+/// names of smart books start with this prefix, so they can't be confused with books.
+pub const SMART_BOOK_PREFIX: &str = "@";
+
+/// a query saved by name, behaving like a read-only book whose contacts are found at every reading.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SmartBook {
+    /// book where the contacts are searched, all contacts if absent.
+    pub book: Option<String>,
+    /// properties in vcard syntax, example: 'ADR:;;;Lyon' or only 'TEL'.
+    pub filters: Vec<String>,
+    /// how the filters are combined to match a contact.
+    pub operator: LogicalOperator,
+    /// values of the filters only need to be contained in the values of the contact.
+    pub forgive: bool,
+    /// tags the contacts must all have.
+    pub tags: Vec<String>,
+}
+
+impl SmartBook {
+    /// the filters as properties.
+    pub fn filter_properties(&self) -> Result<Vec<Property>, ErrorContactManager> {
+        let mut properties = Vec::new();
+        for filter in &self.filters {
+            if filter.contains(':') {
+                properties.push(Property::create_from_str(&format!("{filter}\n"))?);
+            } else {
+                properties.push(Property::default(filter));
+            }
+        }
+        Ok(properties)
+    }
+    /// the contacts matched by the query now.
+    pub(crate) fn evaluate(&self, app_name: &str) -> Result<Vec<Vcard>, ErrorContactManager> {
+        let mut vcards = read_contacts(self.book.as_deref(), false, app_name)?;
+        if !self.filters.is_empty() {
+            vcards = filter_vcards_by_properties(
+                &vcards,
+                &self.filter_properties()?,
+                self.forgive,
+                &self.operator,
+            )?;
+        }
+        vcards.retain(|v| self.tags.iter().all(|tag| has_tag(v, tag)));
+        Ok(vcards)
+    }
+}
+
+/// true if the name is the one of a smart book.
+pub fn is_smart_book(book_name: &str) -> bool {
+    book_name.starts_with(SMART_BOOK_PREFIX)
+}
+
+/// path of the file of a smart book, checking the name.
+fn smart_book_path(app_name: &str, name: &str) -> Result<PathBuf, ErrorContactManager> {
+    let file = name.strip_prefix(SMART_BOOK_PREFIX).ok_or_else(|| {
+        ErrorContactManager::SmartBook(format!("the name {name} must start with {SMART_BOOK_PREFIX}"))
+    })?;
+    if file.is_empty() || file.contains('/') {
+        return Err(ErrorContactManager::SmartBook(format!(
+            "{name} is not a valid name"
+        )));
+    }
+    Ok(smart_books_directory(app_name)?.join(format!("{file}.toml")))
+}
+
+/// will return the names of the saved smart books, with their prefix, sorted.
+pub fn smart_books_names(app_name: &str) -> Result<Vec<String>, ErrorContactManager> {
+    let mut names = Vec::new();
+    for entry in fs::read_dir(smart_books_directory(app_name)?)? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|e| e == "toml") {
+            if let Some(stem) = path.file_stem().and_then(|s| s.to_str()) {
+                names.push(format!("{SMART_BOOK_PREFIX}{stem}"));
+            }
+        }
+    }
+    names.sort();
+    Ok(names)
+}
+
+/// read the query of a smart book.
+pub fn smart_book(app_name: &str, name: &str) -> Result<SmartBook, ErrorContactManager> {
+    let path = smart_book_path(app_name, name)?;
+    if !path.exists() {
+        return Err(ErrorContactManager::SmartBook(format!(
+            "{name} does not exist"
+        )));
+    }
+    toml::from_str(&fs::read_to_string(path)?)
+        .map_err(|e| ErrorContactManager::SmartBook(e.to_string()))
+}
+
+/// save a query as a smart book, replacing the one of the same name.
+/// The query must search in a book, not in another smart book.
+pub fn save_smart_book(
+    store: &Store,
+    name: &str,
+    query: &SmartBook,
+) -> Result<(), ErrorContactManager> {
+    check_write(store)?;
+    if let Some(book) = query.book.as_deref().filter(|b| is_smart_book(b)) {
+        return Err(ErrorContactManager::SmartBook(format!(
+            "{name} can not search in the smart book {book}"
+        )));
+    }
+    // refuse filters that could not be read later.
+    query.filter_properties()?;
+    let _lock = lock_store(store)?;
+    let content =
+        toml::to_string(query).map_err(|e| ErrorContactManager::SmartBook(e.to_string()))?;
+    write_atomic(&smart_book_path(store, name)?, &content)
+}
+
+/// delete a smart book, the contacts are not modified.
+pub fn delete_smart_book(store: &Store, name: &str) -> Result<(), ErrorContactManager> {
+    check_write(store)?;
+    let _lock = lock_store(store)?;
+    fs::remove_file(smart_book_path(store, name)?)?;
+    Ok(())
+}
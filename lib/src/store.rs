@@ -0,0 +1,80 @@
+use std::{
+    cell::RefCell,
+    fs::{self, File, OpenOptions},
+    io::Write,
+    path::Path,
+};
+
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+use vcard_parser::vcard::Vcard;
+
+use crate::{
+    error::ErrorContactManager,
+    paths::{path_vcard_file_from_uuid, place_data_file},
+    vcard::stamp_rev,
+};
+
+thread_local! {
+    /// lock file of the store held by this thread, with the number of functions holding it.
+    static LOCK: RefCell<(usize, Option<File>)> = const { RefCell::new((0, None)) };
+}
+
+/// advisory lock over the store, released when dropped.
+/// Other processes and threads wait for it before modifying the contacts. It can be taken again by the same thread.
+pub(crate) struct StoreLock;
+
+/// wait for the other processes to finish their modifications and lock the store.
+pub(crate) fn lock_store(app_name: &str) -> Result<StoreLock, ErrorContactManager> {
+    LOCK.with_borrow_mut(|held| -> Result<StoreLock, ErrorContactManager> {
+        if held.0 == 0 {
+            // each thread opens the file, so the lock is also waited for by the other threads.
+            let file = OpenOptions::new()
+                .create(true)
+                .truncate(false)
+                .write(true)
+                .open(place_data_file(app_name, ".lock")?)?;
+            file.lock()?;
+            held.1 = Some(file);
+        }
+        held.0 += 1;
+        Ok(StoreLock)
+    })
+}
+
+impl Drop for StoreLock {
+    fn drop(&mut self) {
+        LOCK.with_borrow_mut(|held| {
+            held.0 -= 1;
+            if held.0 == 0 {
+                // closing the file releases the lock.
+                held.1 = None;
+            }
+        })
+    }
+}
+
+/// write the content in a temporary file then rename it, so the file is never left truncated.
+/// The temporary file is next to the file, with the extension ".tmp" added to its name.
+pub fn write_atomic(path: &Path, content: &str) -> Result<(), ErrorContactManager> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let mut file = File::create(&tmp)?;
+    file.write_all(content.as_bytes())?;
+    file.sync_all()?;
+    fs::rename(tmp, path)?;
+    Ok(())
+}
+
+/// stamp the contact with the time of the modification and write it.
+pub(crate) fn write_vcard(path: &Path, vcard: &mut Vcard) -> Result<(), ErrorContactManager> {
+    stamp_rev(vcard)?;
+    write_atomic(path, &vcard.to_string())
+}
+
+/// tag identifying the content of a contact. It changes every time the contact is modified.
+/// The tag is a hash of the content, it can be kept and compared with a tag taken by another version of the library.
+pub fn etag(app_name: &str, uuid: &Uuid) -> Result<String, ErrorContactManager> {
+    let content = fs::read_to_string(path_vcard_file_from_uuid(uuid, None, app_name)?)?;
+    Ok(format!("{:x}", Sha256::digest(content.as_bytes())))
+}
@@ -0,0 +1,347 @@
+use vcard_parser::{
+    constants::PropertyName,
+    traits::{HasCardinality, HasName, HasParameters, HasValue},
+    vcard::{parameter::Parameter, property::Property, Vcard},
+};
+
+use uuid::Uuid;
+
+use crate::error::ErrorContactManager;
+
+/// identifier of a property instance: the local id and the id of the source in CLIENTPIDMAP, if any.
+pub type Pid = (u32, Option<u32>);
+
+/// properties never merged, they are managed by the library or describe the vcard itself.
+const NO_MERGE: [&str; 6] = ["BEGIN", "END", "VERSION", "UID", "REV", "CLIENTPIDMAP"];
+
+/// the PIDs of a property.
+pub fn pids(property: &Property) -> Vec<Pid> {
+    property
+        .get_parameters()
+        .iter()
+        .filter(|p| matches!(p, Parameter::ParameterPid(_)))
+        .flat_map(|p| {
+            p.get_value()
+                .to_string()
+                .split(',')
+                .filter_map(parse_pid)
+                .collect::<Vec<Pid>>()
+        })
+        .collect()
+}
+
+fn parse_pid(pid: &str) -> Option<Pid> {
+    match pid.trim().split_once('.') {
+        Some((local, source)) => Some((local.parse().ok()?, Some(source.parse().ok()?))),
+        None => Some((pid.trim().parse().ok()?, None)),
+    }
+}
+
+/// replace the PIDs of a property.
+fn set_pids(property: &mut Property, pids: &[Pid]) -> Result<(), ErrorContactManager> {
+    let mut parameters = property.get_parameters();
+    parameters.retain(|p| !matches!(p, Parameter::ParameterPid(_)));
+    if !pids.is_empty() {
+        let value = pids
+            .iter()
+            .map(|(local, source)| match source {
+                Some(source) => format!("{local}.{source}"),
+                None => local.to_string(),
+            })
+            .collect::<Vec<String>>()
+            .join(",");
+        parameters.push(Parameter::try_from(format!(";PID={value}").as_str())?);
+    }
+    property.set_parameters(parameters);
+    Ok(())
+}
+
+/// the entries of CLIENTPIDMAP: id of the source and URI of the source.
+pub fn client_pid_map(vcard: &Vcard) -> Vec<(u32, String)> {
+    vcard
+        .get_properties()
+        .iter()
+        .filter(|p| p.name() == PropertyName::CLIENTPIDMAP)
+        .filter_map(|p| {
+            let value = p.get_value().to_string();
+            let (id, uri) = value.split_once(';')?;
+            Some((id.trim().parse().ok()?, uri.trim().to_string()))
+        })
+        .collect()
+}
+
+/// id of the source in the CLIENTPIDMAP of the vcard, adding the source if it is absent.
+pub fn source_id(vcard: &mut Vcard, source: &str) -> Result<u32, ErrorContactManager> {
+    let map = client_pid_map(vcard);
+    if let Some((id, _)) = map.iter().find(|(_, uri)| uri == source) {
+        return Ok(*id);
+    }
+    let id = map.iter().map(|(id, _)| *id).max().unwrap_or(0) + 1;
+    vcard.set_property(&Property::create_from_str(&format!(
+        "CLIENTPIDMAP:{id};{source}\n"
+    ))?)?;
+    Ok(id)
+}
+
+/// only properties which can be present multiple times can have a PID.
+fn can_have_pid(property: &Property) -> bool {
+    property.cardinality() == "MULTIPLE" && !NO_MERGE.contains(&property.name())
+}
+
+/// true if the properties have the same name, value and parameters, whatever their PIDs.
+fn same_content(a: &Property, b: &Property) -> bool {
+    let without_pids = |p: &Property| {
+        let mut p = p.clone();
+        let mut parameters = p.get_parameters();
+        parameters.retain(|p| !matches!(p, Parameter::ParameterPid(_)));
+        p.set_parameters(parameters);
+        p
+    };
+    a.name() == b.name() && without_pids(a) == without_pids(b)
+}
+
+/// true if the properties are two versions of the same property instance.
+/// Properties which can be present multiple times are recognized by their PIDs, or by their value if one has no PID.
+/// The others are recognized by their name.
+fn same_instance(a: &Property, b: &Property) -> bool {
+    if a.name() != b.name() {
+        return false;
+    }
+    if !can_have_pid(a) {
+        return true;
+    }
+    let (pids_a, pids_b) = (pids(a), pids(b));
+    if pids_a.is_empty() || pids_b.is_empty() {
+        a.get_value().to_string() == b.get_value().to_string()
+    } else {
+        pids_a.iter().any(|pid| pids_b.contains(pid))
+    }
+}
+
+/// give a PID of the source to every property that doesn't have one from this source yet,
+/// so the properties can be recognized when merging another version of the vcard.
+/// A property with the same content as in the previous version given by the source takes back its PID.
+pub fn assign_pids(
+    vcard: &mut Vcard,
+    source: &str,
+    previous: Option<&Vcard>,
+) -> Result<(), ErrorContactManager> {
+    // PIDs of the source in the previous version, with their property.
+    let previous: Vec<(Property, u32)> = match previous {
+        Some(previous) => {
+            let id = client_pid_map(previous)
+                .into_iter()
+                .find(|(_, uri)| uri == source)
+                .map(|(id, _)| id);
+            previous
+                .get_properties()
+                .into_iter()
+                .filter_map(|p| {
+                    let local = pids(&p)
+                        .into_iter()
+                        .find(|(_, s)| s.is_some() && *s == id)?
+                        .0;
+                    Some((p, local))
+                })
+                .collect()
+        }
+        None => Vec::new(),
+    };
+    let source = source_id(vcard, source)?;
+    let mut taken: Vec<u32> = vcard
+        .get_properties()
+        .iter()
+        .flat_map(pids)
+        .filter(|(_, s)| *s == Some(source))
+        .map(|(local, _)| local)
+        .collect();
+    let mut next = taken
+        .iter()
+        .chain(previous.iter().map(|(_, local)| local))
+        .max()
+        .copied()
+        .unwrap_or(0);
+    for property in vcard.get_properties() {
+        let mut current = pids(&property);
+        if !can_have_pid(&property) || current.iter().any(|(_, s)| *s == Some(source)) {
+            continue;
+        }
+        let local = match previous
+            .iter()
+            .find(|(p, local)| !taken.contains(local) && same_content(p, &property))
+        {
+            Some((_, local)) => *local,
+            None => {
+                next += 1;
+                next
+            }
+        };
+        taken.push(local);
+        // PIDs without source are only meaningful inside this version of the vcard.
+        current.retain(|(_, s)| s.is_some());
+        current.push((local, Some(source)));
+        let mut new = property.clone();
+        set_pids(&mut new, &current)?;
+        vcard.remove_property(&property)?;
+        vcard.set_property(&new)?;
+    }
+    Ok(())
+}
+
+/// the properties to merge of a vcard, with the sources of their PIDs translated to the ids of the merged version.
+fn translated(vcard: &Vcard, merged: &mut Vcard) -> Result<Vec<Property>, ErrorContactManager> {
+    // id of a source in the vcard, with its id in the merged version.
+    let mut sources = Vec::new();
+    for (id, uri) in client_pid_map(vcard) {
+        sources.push((id, source_id(merged, &uri)?));
+    }
+    let mut properties = Vec::new();
+    for property in vcard.get_properties() {
+        if NO_MERGE.contains(&property.name()) {
+            continue;
+        }
+        let translated: Vec<Pid> = pids(&property)
+            .into_iter()
+            .filter_map(|(local, source)| {
+                let source = sources.iter().find(|(r, _)| Some(*r) == source)?.1;
+                Some((local, Some(source)))
+            })
+            .collect();
+        let mut property = property.clone();
+        set_pids(&mut property, &translated)?;
+        properties.push(property);
+    }
+    Ok(properties)
+}
+
+/// the property with the PIDs of both versions.
+fn with_pids_of(property: &Property, other: &Property) -> Result<Property, ErrorContactManager> {
+    let mut all = pids(property);
+    for pid in pids(other) {
+        if !all.contains(&pid) {
+            all.push(pid);
+        }
+    }
+    let mut property = property.clone();
+    if can_have_pid(&property) {
+        set_pids(&mut property, &all)?;
+    }
+    Ok(property)
+}
+
+/// merge the version of a vcard given by a source with the stored version, property by property, following RFC 6350 section 7.
+/// Properties are matched by their PIDs, the sources being translated through the CLIENTPIDMAP of each version.
+/// The previous version given by the source, if known, tells which side modified a property since the last merge:
+/// - a property modified by the source only takes its new content, a property modified locally only is kept.
+/// - a property modified on both sides keeps its local content, the content of the source is added if the property can be present multiple times.
+/// - a property deleted by the source is deleted if it was not modified locally, a property deleted locally stays deleted if the source did not modify it.
+///
+/// Without previous version, the properties with a PID of the source which are not in its version anymore were deleted by the source.
+pub fn merge(
+    local: &Vcard,
+    remote: &Vcard,
+    previous: Option<&Vcard>,
+    source: &str,
+) -> Result<Vcard, ErrorContactManager> {
+    let mut merged = local.clone();
+    let source = source_id(&mut merged, source)?;
+    let remote = translated(remote, &mut merged)?;
+    let previous_properties = match previous {
+        Some(previous) => translated(previous, &mut merged)?,
+        None => Vec::new(),
+    };
+    let previous_of = |property: &Property| {
+        previous_properties
+            .iter()
+            .find(|p| same_instance(p, property))
+    };
+    for property in remote.iter() {
+        let before = previous_of(property);
+        let present = merged
+            .get_properties()
+            .into_iter()
+            .find(|p| same_instance(p, property));
+        let modified_by_source = !before.is_some_and(|b| same_content(b, property));
+        match present {
+            // deleted locally, added again only if the source modified it.
+            None => {
+                if modified_by_source {
+                    merged.set_property(property)?;
+                }
+            }
+            Some(present) => {
+                let modified_locally = !before.is_some_and(|b| same_content(b, &present));
+                if same_content(&present, property) || !modified_by_source {
+                    // the PIDs of the source are kept to recognize the property at the next merge.
+                    let kept = with_pids_of(&present, property)?;
+                    if kept != present {
+                        merged.remove_property(&present)?;
+                        merged.set_property(&kept)?;
+                    }
+                } else if !modified_locally {
+                    merged.remove_property(&present)?;
+                    merged.set_property(&with_pids_of(property, &present)?)?;
+                } else if can_have_pid(property) {
+                    // both versions are kept, the local one is not the instance of the source anymore.
+                    let mut local_pids = pids(&present);
+                    local_pids.retain(|(_, s)| *s != Some(source));
+                    let mut kept = present.clone();
+                    set_pids(&mut kept, &local_pids)?;
+                    merged.remove_property(&present)?;
+                    merged.set_property(&kept)?;
+                    merged.set_property(property)?;
+                }
+            }
+        }
+    }
+    // properties deleted by the source.
+    for property in merged.get_properties() {
+        if NO_MERGE.contains(&property.name()) || remote.iter().any(|r| same_instance(r, &property))
+        {
+            continue;
+        }
+        let deleted = match (previous, previous_of(&property)) {
+            (Some(_), Some(before)) => same_content(before, &property),
+            (Some(_), None) => false,
+            (None, _) => pids(&property).iter().any(|(_, s)| *s == Some(source)),
+        };
+        if deleted {
+            merged.remove_property(&property)?;
+        }
+    }
+    Ok(merged)
+}
+
+/// the uid of the vcard imported before from the source with the same full name, for a vcard given without uid.
+pub fn known_uuid(vcard: &Vcard, previous: &[Vcard]) -> Option<Uuid> {
+    let fullname = vcard
+        .get_property_by_name(PropertyName::FN)?
+        .get_value()
+        .to_string();
+    previous
+        .iter()
+        .filter(|v| {
+            v.get_property_by_name(PropertyName::FN)
+                .is_some_and(|p| p.get_value().to_string() == fullname)
+        })
+        .find_map(|v| {
+            Uuid::try_parse(
+                &v.get_property_by_name(PropertyName::UID)?
+                    .get_value()
+                    .to_string(),
+            )
+            .ok()
+        })
+}
+
+/// true if the two versions given by a source have the same properties, whatever their revision.
+pub fn same_version(a: &Vcard, b: &Vcard) -> bool {
+    let properties = |v: &Vcard| {
+        v.get_properties()
+            .into_iter()
+            .filter(|p| !NO_MERGE.contains(&p.name()))
+            .collect::<Vec<Property>>()
+    };
+    let (a, b) = (properties(a), properties(b));
+    a.len() == b.len() && a.iter().all(|p| b.iter().any(|o| same_content(p, o)))
+}
@@ -0,0 +1,187 @@
+use uuid::Uuid;
+use vcard_parser::{
+    constants::PropertyName,
+    traits::{HasName, HasParameters, HasValue},
+    vcard::{property::Property, Vcard},
+};
+
+use crate::{
+    access::{check_read, Store},
+    changes::{Change, ChangeSet},
+    error::ErrorContactManager,
+    vcard::{read_contacts, uuids_from_vcards, vcard_uuid, vcards_by_uuid},
+};
+
+/// modification of the tags of contacts.
+#[derive(Clone, Debug)]
+pub enum TagOperation {
+    /// add the tags, the tags already present are kept.
+    Add(Vec<String>),
+    /// remove the tags, the other ones are kept.
+    Remove(Vec<String>),
+    /// rename a tag, keeping its place in the list.
+    Rename {
+        /// present name of the tag.
+        from: String,
+        /// new name of the tag.
+        to: String,
+    },
+}
+
+/// the tags of a contact, from every CATEGORIES property.
+pub fn tags(vcard: &Vcard) -> Vec<String> {
+    vcard
+        .get_properties()
+        .iter()
+        .filter(|p| p.name() == PropertyName::CATEGORIES)
+        .flat_map(|p| {
+            p.get_value()
+                .to_string()
+                .split(',')
+                .map(|t| t.trim().to_string())
+                .filter(|t| !t.is_empty())
+                .collect::<Vec<String>>()
+        })
+        .collect()
+}
+
+/// tags are compared without case.
+fn same_tag(a: &str, b: &str) -> bool {
+    a.to_lowercase() == b.to_lowercase()
+}
+
+/// true if the contact has the tag.
+pub fn has_tag(vcard: &Vcard, tag: &str) -> bool {
+    tags(vcard).iter().any(|t| same_tag(t, tag))
+}
+
+/// the changes to apply the operation on the tags of the contacts. Contacts not modified by the operation have no change.
+pub fn tag_changes(
+    app_name: &str,
+    uuids: &Vec<Uuid>,
+    operation: &TagOperation,
+) -> Result<ChangeSet, ErrorContactManager> {
+    tag_changes_of(&vcards_by_uuid(uuids, app_name)?, operation)
+}
+
+/// the changes to apply the operation on the tags of the vcards given, like tag_changes.
+pub fn tag_changes_of(
+    vcards: &[Vcard],
+    operation: &TagOperation,
+) -> Result<ChangeSet, ErrorContactManager> {
+    let new_tags = match operation {
+        TagOperation::Add(tags) => tags.to_owned(),
+        TagOperation::Rename { to, .. } => vec![to.to_owned()],
+        TagOperation::Remove(_) => vec![],
+    };
+    // a comma would be read as the separator of two tags.
+    if let Some(tag) = new_tags.iter().find(|t| t.contains(',') || t.trim().is_empty()) {
+        return Err(ErrorContactManager::InvalidTag(tag.to_owned()));
+    }
+    let mut changes = ChangeSet::new();
+    for vcard in vcards {
+        let old = tags(vcard);
+        let mut new = old.clone();
+        match operation {
+            TagOperation::Add(tags) => {
+                for tag in tags {
+                    if !new.iter().any(|t| same_tag(t, tag)) {
+                        new.push(tag.trim().to_string());
+                    }
+                }
+            }
+            TagOperation::Remove(tags) => new.retain(|t| !tags.iter().any(|tag| same_tag(t, tag))),
+            TagOperation::Rename { from, to } => {
+                for t in new.iter_mut().filter(|t| same_tag(t, from)) {
+                    *t = to.trim().to_string();
+                }
+                // the new name may be present already.
+                let mut seen: Vec<String> = Vec::new();
+                new.retain(|t| {
+                    let first = !seen.iter().any(|s| same_tag(s, t));
+                    seen.push(t.to_owned());
+                    first
+                });
+            }
+        }
+        if new != old {
+            for change in set_tags(vcard, &new)? {
+                changes.push(change);
+            }
+        }
+    }
+    Ok(changes)
+}
+
+/// the changes replacing the tags of a contact, in one CATEGORIES property keeping the parameters of the first one.
+fn set_tags(vcard: &Vcard, tags: &[String]) -> Result<Vec<Change>, ErrorContactManager> {
+    let uuid = vcard_uuid(vcard)?;
+    let mut properties = vcard
+        .get_properties()
+        .into_iter()
+        .filter(|p| p.name() == PropertyName::CATEGORIES);
+    let first = properties.next();
+    let mut changes: Vec<Change> = properties
+        .map(|property| Change::DeleteProperty { uuid, property })
+        .collect();
+    if tags.is_empty() {
+        if let Some(property) = first {
+            changes.insert(0, Change::DeleteProperty { uuid, property });
+        }
+        return Ok(changes);
+    }
+    let mut new = Property::create_from_str(&format!("CATEGORIES:{}\n", tags.join(",")))?;
+    let change = match first {
+        Some(old) => {
+            new.set_parameters(old.get_parameters());
+            Change::ModifyProperty { uuid, old, new }
+        }
+        None => Change::AddProperty {
+            uuid,
+            property: new,
+        },
+    };
+    changes.insert(0, change);
+    Ok(changes)
+}
+
+/// every tag with the number of contacts having it, the most used first, from book or all.
+pub fn tag_cloud(
+    store: &Store,
+    book_name: Option<&str>,
+) -> Result<Vec<(String, usize)>, ErrorContactManager> {
+    check_read(store)?;
+    let mut cloud: Vec<(String, usize)> = Vec::new();
+    for vcard in read_contacts(book_name, false, store)? {
+        let mut counted: Vec<String> = Vec::new();
+        for tag in tags(&vcard) {
+            if counted.iter().any(|t| same_tag(t, &tag)) {
+                continue;
+            }
+            match cloud.iter_mut().find(|(t, _)| same_tag(t, &tag)) {
+                Some((_, nb)) => *nb += 1,
+                None => cloud.push((tag.to_owned(), 1)),
+            }
+            counted.push(tag);
+        }
+    }
+    cloud.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    Ok(cloud)
+}
+
+/// get the contacts having every tag, from book or all.
+/// If recursive, the books nested in the book are searched too.
+pub fn find_uids_by_tags(
+    store: &Store,
+    book_name: Option<&str>,
+    recursive: bool,
+    tags: &[String],
+) -> Result<Vec<Uuid>, ErrorContactManager> {
+    check_read(store)?;
+    let vcards = read_contacts(book_name, recursive, store)?;
+    let vcards = vcards
+        .iter()
+        .filter(|v| tags.iter().all(|tag| has_tag(v, tag)))
+        .collect();
+    uuids_from_vcards(&vcards)
+}
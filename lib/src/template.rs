@@ -0,0 +1,318 @@
+use uuid::Uuid;
+use vcard_parser::{
+    traits::{HasName, HasParameters, HasValue},
+    vcard::{property::Property, Vcard},
+};
+
+use crate::{
+    access::{check_read, Store},
+    changes::{Change, ChangeSet},
+    error::ErrorContactManager,
+    vcard::{vcard_uuid, vcards_by_uuid},
+};
+
+/// names of the components of the structured properties, in their order in the value.
+const COMPONENTS: [(&str, &[&str]); 3] = [
+    ("N", &["family", "given", "additional", "prefix", "suffix"]),
+    (
+        "ADR",
+        &[
+            "pobox", "extended", "street", "locality", "region", "code", "country",
+        ],
+    ),
+    ("GENDER", &["sex", "identity"]),
+];
+
+/// a property whose value is computed from the other properties of each contact.
+/// Placeholders are written between braces:
+/// - `{FN}` is the value of the property FN.
+/// - `{N.family}` or `{N.0}` is a component of a structured property.
+/// - `{N.family|upper}` transforms the value, with `upper`, `lower`, `capitalize` or `trim`.
+///
+/// Braces are written `{{` and `}}`.
+/// Example: `EMAIL;TYPE=work:{N.given|lower}.{N.family|lower}@corp.com`.
+#[derive(Clone, Debug)]
+pub struct Template {
+    /// name and parameters of the property, as written before the value.
+    head: String,
+    /// the value, read once into its parts.
+    parts: Vec<Part>,
+}
+
+impl Template {
+    /// read a template written in vcard syntax, the value can contain placeholders.
+    pub fn parse(template: &str) -> Result<Self, ErrorContactManager> {
+        // a colon can be present in a quoted parameter.
+        let mut quoted = false;
+        let position = template.char_indices().find_map(|(i, c)| match c {
+            '"' => {
+                quoted = !quoted;
+                None
+            }
+            ':' if !quoted => Some(i),
+            _ => None,
+        });
+        let Some(position) = position else {
+            return Err(ErrorContactManager::Template(format!(
+                "{template} has no value, example: 'NAME:{{FN}}'"
+            )));
+        };
+        Ok(Template {
+            head: template[..position].to_string(),
+            parts: parts(&template[position + 1..])?,
+        })
+    }
+    /// read a template of a text only, without name of property, example: '{FN} <{EMAIL}>'.
+    /// It is rendered with render_value.
+    pub fn display(value: &str) -> Result<Self, ErrorContactManager> {
+        Ok(Template {
+            head: String::new(),
+            parts: parts(value)?,
+        })
+    }
+    /// the value for a contact. None if a property used by the template is absent from the contact.
+    pub fn render_value(&self, vcard: &Vcard) -> Result<Option<String>, ErrorContactManager> {
+        let mut value = String::new();
+        for part in &self.parts {
+            match part {
+                Part::Text(text) => value.push_str(text),
+                Part::Placeholder(placeholder) => match placeholder.value(vcard)? {
+                    Some(v) => value.push_str(&v),
+                    None => return Ok(None),
+                },
+            }
+        }
+        Ok(Some(value))
+    }
+    /// the property for a contact. None if a property used by the template is absent from the contact.
+    pub fn render(&self, vcard: &Vcard) -> Result<Option<Property>, ErrorContactManager> {
+        let Some(value) = self.render_value(vcard)? else {
+            return Ok(None);
+        };
+        Ok(Some(Property::create_from_str(&format!(
+            "{}:{value}\n",
+            self.head
+        ))?))
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Part {
+    Text(String),
+    Placeholder(Placeholder),
+}
+
+#[derive(Clone, Debug)]
+struct Placeholder {
+    property: String,
+    component: Option<usize>,
+    filters: Vec<Filter>,
+}
+
+#[derive(Clone, Debug)]
+enum Filter {
+    Upper,
+    Lower,
+    Capitalize,
+    Trim,
+}
+
+impl Placeholder {
+    fn parse(inner: &str) -> Result<Self, ErrorContactManager> {
+        let mut pipes = inner.split('|');
+        let reference = pipes.next().unwrap_or_default().trim();
+        let (property, component) = match reference.split_once('.') {
+            Some((property, component)) => (property.to_uppercase(), Some(component)),
+            None => (reference.to_uppercase(), None),
+        };
+        if property.is_empty() {
+            return Err(ErrorContactManager::Template(format!(
+                "{{{inner}}} has no property"
+            )));
+        }
+        let component = match component {
+            None => None,
+            Some(c) => Some(match c.parse::<usize>() {
+                Ok(index) => index,
+                Err(_) => COMPONENTS
+                    .iter()
+                    .find(|(name, _)| name == &property)
+                    .and_then(|(_, names)| names.iter().position(|n| n.eq_ignore_ascii_case(c)))
+                    .ok_or_else(|| {
+                        ErrorContactManager::Template(format!(
+                            "{c} is not a component of {property}"
+                        ))
+                    })?,
+            }),
+        };
+        let mut filters = Vec::new();
+        for filter in pipes {
+            filters.push(match filter.trim() {
+                "upper" => Filter::Upper,
+                "lower" => Filter::Lower,
+                "capitalize" => Filter::Capitalize,
+                "trim" => Filter::Trim,
+                f => {
+                    return Err(ErrorContactManager::Template(format!(
+                        "{f} is not a filter, use upper, lower, capitalize or trim"
+                    )))
+                }
+            });
+        }
+        Ok(Placeholder {
+            property,
+            component,
+            filters,
+        })
+    }
+    fn value(&self, vcard: &Vcard) -> Result<Option<String>, ErrorContactManager> {
+        let Some(property) = vcard.get_property_by_name(&self.property) else {
+            return Ok(None);
+        };
+        let value = property.get_value().to_string();
+        let mut value = match self.component {
+            Some(index) => match components(&value).get(index) {
+                Some(component) => component.to_owned(),
+                None => return Ok(None),
+            },
+            None => value,
+        };
+        for filter in &self.filters {
+            value = match filter {
+                Filter::Upper => value.to_uppercase(),
+                Filter::Lower => value.to_lowercase(),
+                Filter::Trim => value.trim().to_string(),
+                Filter::Capitalize => capitalize(&value),
+            };
+        }
+        Ok(Some(value))
+    }
+}
+
+/// the parts of a value, text or placeholder.
+fn parts(value: &str) -> Result<Vec<Part>, ErrorContactManager> {
+    let mut parts = Vec::new();
+    let mut text = String::new();
+    let mut chars = value.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                text.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                text.push('}');
+            }
+            '{' => {
+                let mut inner = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => inner.push(c),
+                        None => {
+                            return Err(ErrorContactManager::Template(format!(
+                                "a brace is not closed in {}",
+                                value
+                            )))
+                        }
+                    }
+                }
+                if !text.is_empty() {
+                    parts.push(Part::Text(std::mem::take(&mut text)));
+                }
+                parts.push(Part::Placeholder(Placeholder::parse(&inner)?));
+            }
+            '}' => {
+                return Err(ErrorContactManager::Template(format!(
+                    "a brace is closed without being opened in {}",
+                    value
+                )))
+            }
+            c => text.push(c),
+        }
+    }
+    if !text.is_empty() {
+        parts.push(Part::Text(text));
+    }
+    Ok(parts)
+}
+
+/// split a structured value on the semicolons not escaped.
+fn components(value: &str) -> Vec<String> {
+    let mut components = vec![String::new()];
+    let mut escaped = false;
+    for c in value.chars() {
+        if c == ';' && !escaped {
+            components.push(String::new());
+        } else {
+            components.last_mut().expect("never empty").push(c);
+        }
+        escaped = c == '\\' && !escaped;
+    }
+    components
+}
+
+/// uppercase the first letter of each word, lowercase the others.
+fn capitalize(value: &str) -> String {
+    let mut capitalized = String::new();
+    let mut start = true;
+    for c in value.chars() {
+        if start {
+            capitalized.extend(c.to_uppercase());
+        } else {
+            capitalized.extend(c.to_lowercase());
+        }
+        start = !c.is_alphanumeric();
+    }
+    capitalized
+}
+
+/// the changes setting the templated property on the contacts, to review them before applying.
+/// The first property with the same name and parameters is modified, or the property is added.
+/// Also return the contacts skipped because a property used by the template is absent.
+pub fn bulk_set_changes(
+    store: &Store,
+    uuids: &Vec<Uuid>,
+    template: &Template,
+) -> Result<(ChangeSet, Vec<Uuid>), ErrorContactManager> {
+    check_read(store)?;
+    template_changes(&vcards_by_uuid(uuids, store)?, template)
+}
+
+/// the changes setting the templated property on the vcards given, like bulk_set_changes.
+pub fn template_changes(
+    vcards: &[Vcard],
+    template: &Template,
+) -> Result<(ChangeSet, Vec<Uuid>), ErrorContactManager> {
+    let mut changes = ChangeSet::new();
+    let mut skipped = Vec::new();
+    for vcard in vcards {
+        let uuid = vcard_uuid(vcard)?;
+        let Some(new) = template.render(vcard)? else {
+            skipped.push(uuid);
+            continue;
+        };
+        let old = vcard.get_properties().into_iter().find(|p| {
+            p.name() == new.name()
+                && new
+                    .get_parameters()
+                    .iter()
+                    .all(|param| p.get_parameters().contains(param))
+        });
+        match old {
+            Some(old) if old.get_value() == new.get_value() => {}
+            Some(old) => {
+                // keep the other parameters, like the PID.
+                let mut new = new;
+                new.set_parameters(old.get_parameters());
+                changes.push(Change::ModifyProperty { uuid, old, new })
+            }
+            None => changes.push(Change::AddProperty {
+                uuid,
+                property: new,
+            }),
+        }
+    }
+    Ok((changes, skipped))
+}
@@ -1,5 +1,6 @@
 use std::{fs, path::PathBuf};
 
+use chrono::{DateTime, NaiveDateTime, Utc};
 use uuid::Uuid;
 use vcard_parser::{
     constants::PropertyName,
@@ -14,12 +15,38 @@ use vcard_parser::{
 };
 
 use crate::{
-    paths::{book_directory, contacts_directory},
+    paths::{book_directory, contacts_directory, sub_books_names},
+    smart::{is_smart_book, smart_book},
     ErrorContactManager,
 };
 /// Property that the user should not have write access to for simplicity.
 pub const PROPERTY_NO_MODIFICATION_BY_USER: [&str; 4] = ["REV", "UID", "BEGIN", "END"];
 
+/// format of REV written by the library.
+const REV_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+/// set REV to the current time.
+pub(crate) fn stamp_rev(vcard: &mut Vcard) -> Result<(), ErrorContactManager> {
+    if let Some(rev) = vcard.get_property_by_name(PropertyName::REV) {
+        vcard.remove_property(&rev)?;
+    }
+    let rev = format!("REV:{}\n", Utc::now().format(REV_FORMAT));
+    vcard.set_property(&Property::create_from_str(&rev)?)?;
+    Ok(())
+}
+
+/// time of the last modification of the vcard, if REV is present and valid.
+pub fn rev(vcard: &Vcard) -> Option<DateTime<Utc>> {
+    let value = vcard
+        .get_property_by_name(PropertyName::REV)?
+        .get_value()
+        .to_string();
+    NaiveDateTime::parse_from_str(&value, REV_FORMAT)
+        .map(|rev| rev.and_utc())
+        .or_else(|_| DateTime::parse_from_rfc3339(&value).map(|rev| rev.to_utc()))
+        .ok()
+}
+
 /// finding vcards by uuids, read directly the file with the uuid name instead of parsing every contacts like read_contacts.
 pub(crate) fn vcards_by_uuid(
     uuids: &Vec<Uuid>,
@@ -120,16 +147,43 @@ pub(crate) fn vcard_uuid(vcard: &Vcard) -> Result<Uuid, ErrorContactManager> {
     )?)
 }
 
+/// read the contacts of a book or of all, with the contacts of the books nested in the book if recursive.
+/// A contact present in several of these books is given once.
 pub(crate) fn read_contacts(
     book_name: Option<&str>,
+    recursive: bool,
     app_name: &str,
 ) -> Result<Vec<Vcard>, ErrorContactManager> {
+    let mut vcards = read_book(book_name, app_name)?;
+    if let Some(book_name) = book_name.filter(|_| recursive) {
+        let mut uuids = uuids_from_vcards(&vcards.iter().collect())?;
+        for sub_book in sub_books_names(app_name, book_name)? {
+            for vcard in read_book(Some(&sub_book), app_name)? {
+                let uuid = vcard_uuid(&vcard)?;
+                if !uuids.contains(&uuid) {
+                    uuids.push(uuid);
+                    vcards.push(vcard);
+                }
+            }
+        }
+    }
+    Ok(vcards)
+}
+
+fn read_book(book_name: Option<&str>, app_name: &str) -> Result<Vec<Vcard>, ErrorContactManager> {
+    // smart books have no directory, their contacts are found by their query.
+    if let Some(name) = book_name.filter(|name| is_smart_book(name)) {
+        return smart_book(app_name, name)?.evaluate(app_name);
+    }
     let dir = path_vcards(app_name, book_name)?;
     let files = fs::read_dir(&dir)?;
     let mut paths = Vec::new();
     for file in files {
         let p = file?.path();
-        paths.push(p)
+        // books can contain other files, like the read-only marker.
+        if p.extension().is_some_and(|e| e == "vcf") {
+            paths.push(p)
+        }
     }
     check_validity_vcards(&paths)
 }
@@ -170,14 +224,16 @@ fn check_validity_vcards(paths: &Vec<PathBuf>) -> Result<Vec<Vcard>, ErrorContac
 }
 #[cfg(feature = "clap")]
 use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
 /// Logic Operator.
 #[cfg_attr(feature = "clap", derive(ValueEnum))]
-#[derive(Clone, Default, Debug)]
+#[derive(Clone, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum LogicalOperator {
-    /// Property AND Property must be present
+    /// Property OR Property must be present
     #[default]
     Or,
-    /// Property OR Property must be present
+    /// Property AND Property must be present
     And,
     /// Property must NOT be present.
     Not,